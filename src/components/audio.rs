@@ -0,0 +1,58 @@
+use bevy::prelude::*;
+
+/// Audio channels, each with its own volume
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AudioChannel {
+    Music,
+    Sfx,
+}
+
+/// Background music track, fading in or out for crossfades
+#[derive(Component, Debug)]
+pub struct MusicTrack {
+    path: &'static str,
+    fade: f32,
+    fading_out: bool,
+}
+
+impl MusicTrack {
+    /// Create a track that fades in from silence
+    pub fn new(path: &'static str) -> Self {
+        Self {
+            path,
+            fade: 0.0,
+            fading_out: false,
+        }
+    }
+
+    /// Getter for the track's asset path
+    pub fn path(&self) -> &'static str {
+        self.path
+    }
+
+    /// Getter for the current fade level, from 0 to 1
+    pub fn fade(&self) -> f32 {
+        self.fade
+    }
+
+    /// Check if the track is fading out
+    pub fn fading_out(&self) -> bool {
+        self.fading_out
+    }
+
+    /// Start fading the track out
+    pub fn fade_out(&mut self) {
+        self.fading_out = true;
+    }
+
+    /// Step the fade towards its target, returning true once the track has faded out
+    pub fn step(&mut self, amount: f32) -> bool {
+        if self.fading_out {
+            self.fade = (self.fade - amount).max(0.0);
+            self.fade == 0.0
+        } else {
+            self.fade = (self.fade + amount).min(1.0);
+            false
+        }
+    }
+}
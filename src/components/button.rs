@@ -3,43 +3,30 @@ use parse_display::Display;
 
 // Modules
 use crate::{
-    components::menu::MenuSelection,
     error::{Error, ErrorLevel},
+    resources::{Action, GameSettings, InputBindings},
 };
 
+/// Marker for buttons that can't be selected
+#[derive(Component, Debug, Default)]
+pub struct DisabledButton;
+
 /// Start menu buttons
 #[derive(Component, Clone, Copy, PartialEq, Eq, Display, Debug)]
 pub enum StartMenuButton {
+    #[display("Continue")]
+    Continue,
     #[display("Start")]
     Start,
+    #[display("Settings")]
+    Settings,
     #[display("Quit")]
     Quit,
 }
 
 impl StartMenuButton {
     /// Start menu button values
-    const VALUES: [Self; 2] = [Self::Start, Self::Quit];
-}
-
-impl TryFrom<ResMut<'_, MenuSelection>> for StartMenuButton {
-    type Error = Error;
-
-    /// Try to convert a menu selection to a start menu button
-    fn try_from(selection: ResMut<'_, MenuSelection>) -> Result<Self, Error> {
-        // Match the selection index
-        match selection.index() {
-            // Start game
-            0 => Ok(StartMenuButton::Start),
-            // Quit game
-            1 => Ok(StartMenuButton::Quit),
-            // Invalid selection
-            _ => Err(Error::new(
-                ErrorLevel::Error,
-                "Invalid menu selection",
-                None,
-            )),
-        }
-    }
+    const VALUES: [Self; 4] = [Self::Continue, Self::Start, Self::Settings, Self::Quit];
 }
 
 /// Pause menu buttons
@@ -47,59 +34,177 @@ impl TryFrom<ResMut<'_, MenuSelection>> for StartMenuButton {
 pub enum IngameMenuButton {
     #[display("Resume")]
     Resume,
+    #[display("Save")]
+    Save,
+    #[display("Settings")]
+    Settings,
+    #[display("Main Menu")]
+    MainMenu,
     #[display("Quit")]
     Quit,
 }
 
 impl IngameMenuButton {
-    /// Start menu button values
-    const VALUES: [Self; 2] = [Self::Resume, Self::Quit];
-}
-
-impl TryFrom<ResMut<'_, MenuSelection>> for IngameMenuButton {
-    type Error = Error;
-
-    /// Try to convert a menu selection to a in-game menu button
-    fn try_from(selection: ResMut<'_, MenuSelection>) -> Result<Self, Error> {
-        // Match the selection index
-        match selection.index() {
-            // Resume game
-            0 => Ok(IngameMenuButton::Resume),
-            // Quit game
-            1 => Ok(IngameMenuButton::Quit),
-            // Invalid selection
-            _ => Err(Error::new(
-                ErrorLevel::Error,
-                "Invalid in-game menu selection",
-                None,
-            )),
+    /// In-game menu button values
+    const VALUES: [Self; 5] = [
+        Self::Resume,
+        Self::Save,
+        Self::Settings,
+        Self::MainMenu,
+        Self::Quit,
+    ];
+}
+
+/// Game over menu buttons
+#[derive(Component, Clone, Copy, PartialEq, Eq, Display, Debug)]
+pub enum GameOverMenuButton {
+    #[display("Play Again")]
+    Restart,
+    #[display("Main Menu")]
+    MainMenu,
+    #[display("Quit")]
+    Quit,
+}
+
+impl GameOverMenuButton {
+    /// Game over menu button values
+    const VALUES: [Self; 3] = [Self::Restart, Self::MainMenu, Self::Quit];
+}
+
+/// Settings menu buttons
+#[derive(Component, Clone, Copy, PartialEq, Eq, Display, Debug)]
+pub enum SettingsMenuButton {
+    #[display("Master Volume")]
+    MasterVolume,
+    #[display("Music Volume")]
+    MusicVolume,
+    #[display("SFX Volume")]
+    SfxVolume,
+    #[display("Fullscreen")]
+    Fullscreen,
+    #[display("VSync")]
+    Vsync,
+    #[display("Resolution")]
+    Resolution,
+    #[display("Controls")]
+    Controls,
+    #[display("Back")]
+    Back,
+}
+
+impl SettingsMenuButton {
+    /// Settings menu button values
+    const VALUES: [Self; 8] = [
+        Self::MasterVolume,
+        Self::MusicVolume,
+        Self::SfxVolume,
+        Self::Fullscreen,
+        Self::Vsync,
+        Self::Resolution,
+        Self::Controls,
+        Self::Back,
+    ];
+}
+
+impl SettingsMenuButton {
+    /// Volume change for each adjustment
+    const VOLUME_STEP: f32 = 0.1;
+
+    /// Adjust the setting for a button, wrapping sliders around at the ends if requested
+    pub fn adjust(&self, settings: &mut GameSettings, forward: bool, wrap: bool) {
+        // Step a volume slider, rounding to avoid drift
+        let step = |volume: f32| -> f32 {
+            let step: f32 = if forward {
+                Self::VOLUME_STEP
+            } else {
+                -Self::VOLUME_STEP
+            };
+            let stepped: f32 = ((volume + step) / Self::VOLUME_STEP).round() * Self::VOLUME_STEP;
+
+            match (wrap, forward) {
+                (true, true) if volume >= 1.0 => 0.0,
+                (true, false) if volume <= 0.0 => 1.0,
+                _ => stepped,
+            }
+        };
+
+        match self {
+            Self::MasterVolume => settings.set_master_volume(step(settings.master_volume())),
+            Self::MusicVolume => settings.set_music_volume(step(settings.music_volume())),
+            Self::SfxVolume => settings.set_sfx_volume(step(settings.sfx_volume())),
+            Self::Fullscreen => settings.set_fullscreen(!settings.fullscreen()),
+            Self::Vsync => settings.set_vsync(!settings.vsync()),
+            Self::Resolution => settings.cycle_resolution(forward),
+            Self::Controls | Self::Back => {}
+        }
+    }
+
+    /// Get the label for a button, including its current value
+    pub fn label(&self, settings: &GameSettings) -> String {
+        let percent = |volume: f32| -> String { format!("{self}: {:.0}%", volume * 100.0) };
+        let toggle =
+            |enabled: bool| -> String { format!("{self}: {}", if enabled { "On" } else { "Off" }) };
+
+        match self {
+            Self::MasterVolume => percent(settings.master_volume()),
+            Self::MusicVolume => percent(settings.music_volume()),
+            Self::SfxVolume => percent(settings.sfx_volume()),
+            Self::Fullscreen => toggle(settings.fullscreen()),
+            Self::Vsync => toggle(settings.vsync()),
+            Self::Resolution => format!(
+                "{self}: {}x{}",
+                settings.resolution().x,
+                settings.resolution().y
+            ),
+            Self::Controls | Self::Back => self.to_string(),
         }
     }
 }
 
-/// Trait for button indices
-pub trait ButtonIndex {
-    /// Get the index for a button
-    fn index(&self) -> usize;
+/// Key bindings menu buttons
+#[derive(Component, Clone, Copy, PartialEq, Eq, Display, Debug)]
+pub enum BindingsMenuButton {
+    #[display("{0}")]
+    Bind(Action),
+    #[display("Reset to Defaults")]
+    Reset,
+    #[display("Back")]
+    Back,
 }
 
-impl ButtonIndex for StartMenuButton {
-    /// Get the index for a start menu button
-    fn index(&self) -> usize {
+impl BindingsMenuButton {
+    /// Get the label for a button, including the keys bound to its action
+    pub fn label(&self, bindings: &InputBindings, rebinding: Option<Action>) -> String {
         match self {
-            StartMenuButton::Start => 0,
-            StartMenuButton::Quit => 1,
+            Self::Bind(action) if rebinding == Some(*action) => {
+                format!("{action}: press a key")
+            }
+            Self::Bind(action) => {
+                let keys: Vec<String> = bindings
+                    .keys(*action)
+                    .iter()
+                    .map(|key| format!("{key:?}"))
+                    .collect();
+                format!("{action}: {}", keys.join(", "))
+            }
+            Self::Reset | Self::Back => self.to_string(),
         }
     }
 }
 
-impl ButtonIndex for IngameMenuButton {
-    /// Get the index for a in-game menu button
+/// Trait for button indices
+pub trait ButtonIndex {
+    /// Get the index for a button
+    fn index(&self) -> usize;
+}
+
+impl<T: ButtonValues + PartialEq> ButtonIndex for T {
+    /// Get the index for a button from its position in the button values
     fn index(&self) -> usize {
-        match self {
-            IngameMenuButton::Resume => 0,
-            IngameMenuButton::Quit => 1,
-        }
+        Self::values()
+            .iter()
+            .position(|button| button == self)
+            .unwrap_or_default()
     }
 }
 
@@ -109,6 +214,20 @@ pub trait ButtonValues {
     fn values() -> Vec<Self>
     where
         Self: Sized;
+
+    /// Get the button at an index in the button values
+    fn from_index(index: usize) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        Self::values().into_iter().nth(index).ok_or_else(|| {
+            Error::new(
+                ErrorLevel::Error,
+                &format!("Invalid menu selection: {index}"),
+                None,
+            )
+        })
+    }
 }
 
 impl ButtonValues for StartMenuButton {
@@ -124,3 +243,28 @@ impl ButtonValues for IngameMenuButton {
         Self::VALUES.to_vec()
     }
 }
+
+impl ButtonValues for GameOverMenuButton {
+    /// Get the values for a game over menu button
+    fn values() -> Vec<Self> {
+        Self::VALUES.to_vec()
+    }
+}
+
+impl ButtonValues for SettingsMenuButton {
+    /// Get the values for a settings menu button
+    fn values() -> Vec<Self> {
+        Self::VALUES.to_vec()
+    }
+}
+
+impl ButtonValues for BindingsMenuButton {
+    /// Get the values for a key bindings menu button, one per action
+    fn values() -> Vec<Self> {
+        Action::VALUES
+            .into_iter()
+            .map(Self::Bind)
+            .chain([Self::Reset, Self::Back])
+            .collect()
+    }
+}
@@ -0,0 +1,400 @@
+use bevy::{
+    math::curve::{Curve, EaseFunction, EasingCurve},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+use std::iter;
+
+// Modules
+use crate::level::LevelCamera;
+
+/// How the camera follows the player
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum CameraMode {
+    /// Stay where the level places the camera
+    #[default]
+    Fixed,
+    /// Follow the player once they leave a box around the centre of the view, given by its half size
+    Follow { dead_zone: Vec2 },
+    /// Ease after the player, catching up in roughly `smooth_time` seconds
+    SmoothFollow { smooth_time: f32 },
+    /// Ease after a point `distance` ahead of the player, in the direction they're moving
+    LookAhead { distance: f32, smooth_time: f32 },
+}
+
+/// Moves the camera after the player, keeping its view within the level's extents
+#[derive(Component, Debug, Clone, Copy, PartialEq, Default)]
+pub struct CameraController {
+    pub mode: CameraMode,
+    /// Camera position relative to the point it looks at
+    pub offset: Vec3,
+    /// Point the camera looks at
+    pub focus: Vec3,
+    /// Level extents to keep the view within
+    pub extents: Rect,
+    /// Speed of the focus while easing, in world units per second
+    velocity: Vec2,
+}
+
+impl CameraController {
+    /// Place the camera as a level sets it, looking at the start point unless it's fixed
+    pub fn new(camera: &LevelCamera, extents: Rect, start: Vec2) -> Self {
+        Self {
+            mode: camera.mode,
+            offset: camera.position - camera.look_at,
+            focus: match camera.mode {
+                CameraMode::Fixed => camera.look_at,
+                _ => start.extend(camera.look_at.z),
+            },
+            extents,
+            velocity: Vec2::ZERO,
+        }
+    }
+
+    /// Get the half size of the view around the focus, for a vertical field of view in radians
+    pub fn half_view(&self, fov: f32, aspect_ratio: f32) -> Vec2 {
+        let half_height: f32 = self.offset.length() * (fov / 2.0).tan();
+        Vec2::new(half_height * aspect_ratio, half_height)
+    }
+
+    /// Move the focus after the player, keeping a view of the given half size within the extents
+    pub fn follow(&mut self, player: Vec2, velocity: Vec2, half_view: Vec2, delta: f32) {
+        let focus: Vec2 = self.focus.truncate();
+        let target: Vec2 = match self.mode {
+            CameraMode::Fixed => return,
+            CameraMode::Follow { dead_zone } => {
+                player - (player - focus).clamp(-dead_zone, dead_zone)
+            }
+            CameraMode::SmoothFollow { smooth_time } => {
+                self.smooth_damp(focus, player, smooth_time, delta)
+            }
+            CameraMode::LookAhead {
+                distance,
+                smooth_time,
+            } => {
+                let ahead: Vec2 = player + velocity.normalize_or_zero() * distance;
+                self.smooth_damp(focus, ahead, smooth_time, delta)
+            }
+        };
+
+        // Keep the view within the extents, centring it where they're smaller than the view
+        let centre: Vec2 = self.extents.center();
+        let bounded: Vec2 = target.clamp(
+            (self.extents.min + half_view).min(centre),
+            (self.extents.max - half_view).max(centre),
+        );
+        self.focus = bounded.extend(self.focus.z);
+    }
+
+    /// Get the camera's transform, looking at the focus
+    pub fn transform(&self) -> Transform {
+        Transform::from_translation(self.focus + self.offset).looking_at(self.focus, Vec3::Y)
+    }
+
+    /// Ease from one point towards another, as a critically damped spring
+    fn smooth_damp(&mut self, from: Vec2, to: Vec2, smooth_time: f32, delta: f32) -> Vec2 {
+        let omega: f32 = 2.0 / smooth_time;
+        let x: f32 = omega * delta;
+        let decay: f32 = 1.0 / (1.0 + x + 0.48 * x * x + 0.235 * x * x * x);
+        let change: Vec2 = from - to;
+        let pull: Vec2 = (self.velocity + omega * change) * delta;
+        self.velocity = (self.velocity - omega * pull) * decay;
+
+        to + (change + pull) * decay
+    }
+}
+
+/// A shot in a scripted camera move, reached `duration` seconds of game time after the last
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CameraWaypoint {
+    pub position: Vec3,
+    pub look_at: Vec3,
+    pub duration: f32,
+    #[serde(default = "ease_default")]
+    pub ease: EaseFunction,
+}
+
+/// Easing for waypoints that don't set their own
+fn ease_default() -> EaseFunction {
+    EaseFunction::SineInOut
+}
+
+/// A scripted camera move through waypoints, settling back to the camera controller's shot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CameraPath {
+    pub waypoints: Vec<CameraWaypoint>,
+    /// Seconds to ease back to the controller's shot after the last waypoint
+    #[serde(default)]
+    pub settle: f32,
+}
+
+/// Camera position and the point it looks at
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Shot {
+    position: Vec3,
+    look_at: Vec3,
+}
+
+/// Field of view change in progress
+#[derive(Debug, Clone, Copy)]
+struct Zoom {
+    from: f32,
+    to: f32,
+    start: f32,
+    duration: f32,
+    ease: EaseFunction,
+}
+
+/// Scripted camera move in progress
+#[derive(Debug, Clone)]
+struct PathPlayback {
+    path: CameraPath,
+    start: f32,
+    from: Shot,
+}
+
+/// Shake, zoom and scripted moves layered over the camera controller, timed in game time
+#[derive(Component, Debug, Clone, Default)]
+pub struct CameraEffects {
+    /// Shake strength from 0 to 1, wearing off over time
+    trauma: f32,
+    zoom: Option<Zoom>,
+    playback: Option<PathPlayback>,
+    /// Game time the effects were last applied at
+    applied: f32,
+}
+
+impl CameraEffects {
+    /// Trauma worn off per second
+    const TRAUMA_DECAY: f32 = 1.2;
+
+    /// Largest shake offset at full trauma, in world units
+    const SHAKE_OFFSET: f32 = 0.25;
+
+    /// Largest shake roll at full trauma, in radians
+    const SHAKE_ROLL: f32 = 0.05;
+
+    /// How quickly the shake moves, in radians per second
+    const SHAKE_SPEED: f32 = 40.0;
+
+    /// Add trauma, shaking the camera harder and for longer
+    pub fn shake(&mut self, trauma: f32) {
+        self.trauma = (self.trauma + trauma).clamp(0.0, 1.0);
+    }
+
+    /// Ease the field of view from one angle to another, in radians
+    pub fn zoom(&mut self, from: f32, to: f32, duration: f32, ease: EaseFunction, now: f32) {
+        self.zoom = Some(Zoom {
+            from,
+            to,
+            start: now,
+            duration,
+            ease,
+        });
+    }
+
+    /// Play a scripted move, starting from a camera position and the point it looks at
+    pub fn play(&mut self, path: CameraPath, position: Vec3, look_at: Vec3, now: f32) {
+        self.playback = Some(PathPlayback {
+            path,
+            start: now,
+            from: Shot { position, look_at },
+        });
+    }
+
+    /// Get the field of view while zooming, finishing the zoom once it's reached
+    pub fn fov(&mut self, now: f32) -> Option<f32> {
+        let zoom: Zoom = self.zoom?;
+        let progress: f32 = match zoom.duration > 0.0 {
+            true => ((now - zoom.start) / zoom.duration).clamp(0.0, 1.0),
+            false => 1.0,
+        };
+        if progress >= 1.0 {
+            self.zoom = None;
+        }
+
+        Some(EasingCurve::new(zoom.from, zoom.to, zoom.ease).sample_clamped(progress))
+    }
+
+    /// Layer the scripted move and shake over the controller's transform, at a game time
+    pub fn apply(&mut self, transform: &mut Transform, controller: &CameraController, now: f32) {
+        let delta: f32 = (now - self.applied).max(0.0);
+        self.applied = now;
+
+        // Play the scripted move, settling back to the controller's shot
+        if let Some(playback) = &self.playback {
+            let settled = Shot {
+                position: controller.focus + controller.offset,
+                look_at: controller.focus,
+            };
+            match playback.sample(now - playback.start, settled) {
+                Some(shot) => {
+                    *transform = Transform::from_translation(shot.position)
+                        .looking_at(shot.look_at, Vec3::Y);
+                }
+                None => self.playback = None,
+            }
+        }
+
+        // Shake by the square of the trauma, so small knocks stay subtle
+        if self.trauma > 0.0 {
+            let shake: f32 = self.trauma * self.trauma;
+            let phase: f32 = now * Self::SHAKE_SPEED;
+            let offset = Vec3::new(wobble(phase, 0.0), wobble(phase, 1.0), 0.0);
+            transform.translation += transform.rotation * offset * Self::SHAKE_OFFSET * shake;
+            transform.rotate_local_z(wobble(phase, 2.0) * Self::SHAKE_ROLL * shake);
+
+            self.trauma = (self.trauma - Self::TRAUMA_DECAY * delta).max(0.0);
+        }
+    }
+}
+
+impl PathPlayback {
+    /// Get the shot a time into the move, or nothing once it has settled
+    fn sample(&self, mut elapsed: f32, settled: Shot) -> Option<Shot> {
+        let legs = self
+            .path
+            .waypoints
+            .iter()
+            .map(|waypoint| {
+                let shot = Shot {
+                    position: waypoint.position,
+                    look_at: waypoint.look_at,
+                };
+                (shot, waypoint.duration, waypoint.ease)
+            })
+            .chain(iter::once((settled, self.path.settle, ease_default())));
+
+        // Find the leg in progress, cutting straight through those without a duration
+        let mut from: Shot = self.from;
+        for (to, duration, ease) in legs {
+            if elapsed < duration {
+                let progress: f32 = elapsed / duration;
+                return Some(Shot {
+                    position: EasingCurve::new(from.position, to.position, ease)
+                        .sample_clamped(progress),
+                    look_at: EasingCurve::new(from.look_at, to.look_at, ease)
+                        .sample_clamped(progress),
+                });
+            }
+            elapsed -= duration;
+            from = to;
+        }
+
+        None
+    }
+}
+
+/// Smooth pseudo-random movement from -1 to 1, with a different pattern for each channel
+fn wobble(phase: f32, channel: f32) -> f32 {
+    ((phase + channel * 7.1).sin() + (phase * 2.3 + channel * 3.7).sin() * 0.5) / 1.5
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Length of a fixed timestep, in seconds
+    const DELTA: f32 = 1.0 / 64.0;
+
+    /// Half size of the view used by the tests
+    const HALF_VIEW: Vec2 = Vec2::new(4.0, 3.0);
+
+    /// Build a controller in a mode, starting at the origin within extents
+    fn controller(mode: CameraMode, extents: Rect) -> CameraController {
+        let camera = LevelCamera { mode, ..default() };
+        CameraController::new(&camera, extents, Vec2::ZERO)
+    }
+
+    /// Extents much larger than the view
+    fn open() -> Rect {
+        Rect::new(-100.0, -100.0, 100.0, 100.0)
+    }
+
+    /// Get the focus, without its depth
+    fn focus(controller: &CameraController) -> Vec2 {
+        controller.focus.truncate()
+    }
+
+    #[test]
+    fn fixed_cameras_stay_put() {
+        let mut camera: CameraController = controller(CameraMode::Fixed, open());
+        camera.follow(Vec2::new(5.0, 5.0), Vec2::X, HALF_VIEW, DELTA);
+        assert_eq!(camera.focus, LevelCamera::default().look_at);
+    }
+
+    #[test]
+    fn follow_waits_for_the_player_to_leave_the_dead_zone() {
+        let dead_zone = Vec2::new(1.0, 0.5);
+        let mut camera: CameraController = controller(CameraMode::Follow { dead_zone }, open());
+
+        // Moving within the dead zone leaves the camera alone
+        camera.follow(Vec2::new(0.8, -0.4), Vec2::ZERO, HALF_VIEW, DELTA);
+        assert_eq!(focus(&camera), Vec2::ZERO);
+
+        // Leaving it drags the camera along, keeping the player on its edge
+        camera.follow(Vec2::new(3.0, 0.25), Vec2::ZERO, HALF_VIEW, DELTA);
+        assert_eq!(focus(&camera), Vec2::new(2.0, 0.0));
+        camera.follow(Vec2::new(-3.0, -2.0), Vec2::ZERO, HALF_VIEW, DELTA);
+        assert_eq!(focus(&camera), Vec2::new(-2.0, -1.5));
+    }
+
+    #[test]
+    fn smooth_follow_eases_in_without_overshooting() {
+        let smooth_time: f32 = 0.3;
+        let mut camera: CameraController =
+            controller(CameraMode::SmoothFollow { smooth_time }, open());
+        let player = Vec2::new(4.0, 0.0);
+
+        // Closes in a little each step, never passing the player
+        let mut last: f32 = 0.0;
+        for _ in 0..(2.0 / DELTA) as u32 {
+            camera.follow(player, Vec2::ZERO, HALF_VIEW, DELTA);
+            let x: f32 = focus(&camera).x;
+            assert!(x >= last && x <= player.x, "{x} after {last}");
+            last = x;
+        }
+
+        // Well on its way after the smooth time, and there after a few
+        let mut camera: CameraController =
+            controller(CameraMode::SmoothFollow { smooth_time }, open());
+        for _ in 0..(smooth_time / DELTA) as u32 {
+            camera.follow(player, Vec2::ZERO, HALF_VIEW, DELTA);
+        }
+        assert!(focus(&camera).x > player.x / 2.0);
+        assert!(last > player.x - 0.01);
+    }
+
+    #[test]
+    fn look_ahead_leads_the_player() {
+        let mode = CameraMode::LookAhead {
+            distance: 1.5,
+            smooth_time: 0.2,
+        };
+        let mut camera: CameraController = controller(mode, open());
+        for _ in 0..(2.0 / DELTA) as u32 {
+            camera.follow(Vec2::ZERO, Vec2::new(0.0, -3.0), HALF_VIEW, DELTA);
+        }
+        assert!(focus(&camera).distance(Vec2::new(0.0, -1.5)) < 0.01);
+    }
+
+    #[test]
+    fn view_stays_within_the_extents() {
+        let follow = CameraMode::Follow {
+            dead_zone: Vec2::ZERO,
+        };
+
+        // Stops with the view's edge on the extents
+        let mut camera: CameraController = controller(follow, Rect::new(-10.0, -5.0, 10.0, 5.0));
+        camera.follow(Vec2::new(20.0, 20.0), Vec2::ZERO, HALF_VIEW, DELTA);
+        assert_eq!(focus(&camera), Vec2::new(6.0, 2.0));
+        camera.follow(Vec2::new(-20.0, 0.0), Vec2::ZERO, HALF_VIEW, DELTA);
+        assert_eq!(focus(&camera), Vec2::new(-6.0, 0.0));
+
+        // Centres on extents smaller than the view, along each axis
+        let mut camera: CameraController = controller(follow, Rect::new(0.0, -1.0, 20.0, 3.0));
+        camera.follow(Vec2::new(-20.0, -20.0), Vec2::ZERO, HALF_VIEW, DELTA);
+        assert_eq!(focus(&camera), Vec2::new(4.0, 1.0));
+        assert_eq!(camera.focus.z, LevelCamera::default().look_at.z);
+    }
+}
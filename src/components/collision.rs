@@ -0,0 +1,112 @@
+use bevy::prelude::*;
+
+/// Collision shape, centred on the entity's translation
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub enum Collider {
+    /// Axis-aligned box, in world units from the centre to each edge
+    Aabb { half_extents: Vec2 },
+    /// Circle, in world units from the centre
+    Sphere { radius: f32 },
+}
+
+/// Marker component for colliders that block moving entities
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct Solid;
+
+impl Collider {
+    /// Create a box collider from its full size
+    pub fn aabb(size: Vec2) -> Self {
+        Collider::Aabb {
+            half_extents: size / 2.0,
+        }
+    }
+
+    /// Create a sphere collider from its radius
+    pub fn sphere(radius: f32) -> Self {
+        Collider::Sphere { radius }
+    }
+
+    /// Get the bounding box of the collider at a position
+    pub fn bounds(&self, position: Vec2) -> Rect {
+        match *self {
+            Collider::Aabb { half_extents } => Rect::from_center_half_size(position, half_extents),
+            Collider::Sphere { radius } => {
+                Rect::from_center_half_size(position, Vec2::splat(radius))
+            }
+        }
+    }
+
+    /// Get the shortest translation that moves this collider out of another, if they overlap
+    pub fn contact(&self, position: Vec2, other: &Collider, other_position: Vec2) -> Option<Vec2> {
+        match (*self, *other) {
+            (
+                Collider::Aabb { half_extents },
+                Collider::Aabb {
+                    half_extents: other_half,
+                },
+            ) => {
+                let offset: Vec2 = position - other_position;
+                let overlap: Vec2 = half_extents + other_half - offset.abs();
+                if overlap.x <= 0.0 || overlap.y <= 0.0 {
+                    return None;
+                }
+
+                // Push out along the axis with the least overlap
+                match overlap.x < overlap.y {
+                    true => Some(Vec2::new(overlap.x * sign(offset.x), 0.0)),
+                    false => Some(Vec2::new(0.0, overlap.y * sign(offset.y))),
+                }
+            }
+            (
+                Collider::Sphere { radius },
+                Collider::Sphere {
+                    radius: other_radius,
+                },
+            ) => {
+                let offset: Vec2 = position - other_position;
+                let depth: f32 = radius + other_radius - offset.length();
+                (depth > 0.0).then(|| offset.normalize_or(Vec2::X) * depth)
+            }
+            (Collider::Sphere { radius }, Collider::Aabb { half_extents }) => {
+                sphere_aabb_contact(position, radius, other_position, half_extents)
+            }
+            (Collider::Aabb { half_extents }, Collider::Sphere { radius }) => {
+                sphere_aabb_contact(other_position, radius, position, half_extents)
+                    .map(|push| -push)
+            }
+        }
+    }
+}
+
+/// Get the shortest translation that moves a sphere out of a box, if they overlap
+fn sphere_aabb_contact(
+    centre: Vec2,
+    radius: f32,
+    box_centre: Vec2,
+    half_extents: Vec2,
+) -> Option<Vec2> {
+    let offset: Vec2 = centre - box_centre;
+    let closest: Vec2 = offset.clamp(-half_extents, half_extents);
+    let outside: Vec2 = offset - closest;
+
+    // Centre outside the box, push away from the closest point
+    if outside != Vec2::ZERO {
+        let distance: f32 = outside.length();
+        return (distance < radius).then(|| outside / distance * (radius - distance));
+    }
+
+    // Centre inside the box, push out through the nearest edge
+    let depth: Vec2 = half_extents - offset.abs() + Vec2::splat(radius);
+    match depth.x < depth.y {
+        true => Some(Vec2::new(depth.x * sign(offset.x), 0.0)),
+        false => Some(Vec2::new(0.0, depth.y * sign(offset.y))),
+    }
+}
+
+/// Get the direction of a value, treating zero as positive
+fn sign(value: f32) -> f32 {
+    match value < 0.0 {
+        true => -1.0,
+        false => 1.0,
+    }
+}
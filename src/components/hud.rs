@@ -0,0 +1,28 @@
+use bevy::prelude::*;
+use parse_display::Display;
+
+/// Marker component for the HUD overlay
+#[derive(Component, Default)]
+pub struct Hud;
+
+/// HUD readouts
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum HudText {
+    Time,
+    Score,
+    Level,
+    Lives,
+}
+
+impl HudText {
+    /// HUD readout values, in display order
+    pub const VALUES: [Self; 4] = [Self::Time, Self::Score, Self::Level, Self::Lives];
+
+    /// Render the readout for a value
+    pub fn label(&self, value: impl std::fmt::Display) -> String {
+        match self {
+            HudText::Time => value.to_string(),
+            _ => format!("{self}: {value}"),
+        }
+    }
+}
@@ -0,0 +1,5 @@
+use bevy::prelude::*;
+
+/// Marker component for entities spawned from the current level, cleared when it changes
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct LevelEntity;
@@ -1,9 +1,13 @@
-use bevy::{prelude::*, state::state::FreelyMutableState};
+use bevy::prelude::*;
 
 // Modules
-use crate::components::button::{ButtonIndex, ButtonValues};
+use crate::{
+    components::button::{ButtonIndex, ButtonValues, DisabledButton},
+    events::{PlaySfx, SoundEffect},
+    resources::Action,
+};
 
-/// Current selection index for keyboard nav (0/1)
+/// Current selection index for action nav
 #[derive(Resource, Default, Debug)]
 pub struct MenuSelection(usize);
 
@@ -17,6 +21,140 @@ impl MenuSelection {
     pub fn set_index(&mut self, index: usize) {
         self.0 = index;
     }
+
+    /// Move the selection one button forward or back, skipping disabled buttons
+    pub fn step(&mut self, forward: bool, count: usize, wrap: bool, disabled: &[usize]) {
+        let mut index: usize = self.0;
+
+        // Try each button at most once
+        for _ in 0..count {
+            index = match (forward, wrap) {
+                (true, _) if index + 1 < count => index + 1,
+                (true, true) => 0,
+                (false, _) if index > 0 => index - 1,
+                (false, true) => count - 1,
+                // Stop at the ends without wrapping
+                _ => return,
+            };
+
+            if !disabled.contains(&index) {
+                self.0 = index;
+                return;
+            }
+        }
+    }
+}
+
+/// Repeat timing for held menu navigation
+#[derive(Resource, Debug)]
+pub struct MenuRepeat {
+    /// Seconds a direction must be held before it starts repeating
+    pub initial_delay: f32,
+    /// Seconds between each repeat while a direction is held
+    pub repeat_interval: f32,
+    held: Option<Action>,
+    remaining: f32,
+}
+
+impl Default for MenuRepeat {
+    fn default() -> Self {
+        Self {
+            initial_delay: 0.4,
+            repeat_interval: 0.1,
+            held: None,
+            remaining: 0.0,
+        }
+    }
+}
+
+impl MenuRepeat {
+    /// Navigation actions handled by the repeat
+    const ACTIONS: [Action; 2] = [Action::MoveUp, Action::MoveDown];
+
+    /// Get the navigation action to apply this frame, if any
+    pub fn update(&mut self, actions: &ButtonInput<Action>, delta: f32) -> Option<Action> {
+        // A fresh press moves immediately, then waits for the initial delay
+        if let Some(action) = Self::ACTIONS
+            .into_iter()
+            .find(|action| actions.just_pressed(*action))
+        {
+            self.held = Some(action);
+            self.remaining = self.initial_delay;
+            return Some(action);
+        }
+
+        // Stop repeating once the held direction is released
+        let held: Action = match self.held {
+            Some(action) if actions.pressed(action) => action,
+            _ => {
+                self.held = None;
+                return None;
+            }
+        };
+
+        // Repeat each time the timer runs out
+        self.remaining -= delta;
+        if self.remaining <= 0.0 {
+            self.remaining += self.repeat_interval;
+            return Some(held);
+        }
+
+        None
+    }
+}
+
+/// Action waiting for a key press to rebind it
+#[derive(Resource, Default, Debug)]
+pub struct Rebinding {
+    /// Action being rebound
+    action: Option<Action>,
+    /// Keys already held as the capture started, such as the key that started it
+    held: Option<Vec<KeyCode>>,
+    /// Key pressed to rebind the action, bound once it is released
+    key: Option<KeyCode>,
+}
+
+impl Rebinding {
+    /// Wait for a key press to rebind an action
+    pub fn new(action: Action) -> Self {
+        Self {
+            action: Some(action),
+            ..default()
+        }
+    }
+
+    /// Getter for the action being rebound
+    pub fn get(&self) -> Option<Action> {
+        self.action
+    }
+
+    /// Capture a newly pressed key, returning it with the action once it is released
+    pub fn capture(&mut self, keyboard_input: &ButtonInput<KeyCode>) -> Option<(Action, KeyCode)> {
+        let action: Action = self.action?;
+
+        // Ignore the keys held as the capture started, until they are released
+        let held: &mut Vec<KeyCode> = self
+            .held
+            .get_or_insert_with(|| keyboard_input.get_pressed().copied().collect());
+        held.retain(|key| keyboard_input.pressed(*key));
+
+        // Wait for a key that wasn't already held
+        if self.key.is_none() {
+            self.key = keyboard_input
+                .get_just_pressed()
+                .find(|key| !held.contains(key))
+                .copied();
+        }
+
+        // Wait for the key to be released, so it doesn't also trigger its new action
+        match self.key {
+            Some(key) if keyboard_input.just_released(key) => {
+                *self = Self::default();
+                Some((action, key))
+            }
+            _ => None,
+        }
+    }
 }
 
 /// Start menu
@@ -27,15 +165,42 @@ pub struct StartMenu;
 #[derive(Component, Debug, Default)]
 pub struct IngameMenu;
 
+/// Game over menu
+#[derive(Component, Debug, Default)]
+pub struct GameOverMenu;
+
+/// Settings menu
+#[derive(Component, Debug, Default)]
+pub struct SettingsMenu;
+
+/// Key bindings menu
+#[derive(Component, Debug, Default)]
+pub struct BindingsMenu;
+
 /// Trait for menu components
 pub trait Menu {
     type MenuEntity: Component + Default;
-    type MenuButton: Component + ButtonIndex + ButtonValues + std::fmt::Display + Clone;
-    type State: States;
+    type MenuButton: Component + ButtonIndex + ButtonValues + PartialEq + std::fmt::Display + Clone;
+
+    /// Optional heading shown above the buttons
+    const TITLE: Option<&'static str> = None;
+
+    /// Whether navigation wraps around from the last button to the first
+    const WRAP: bool = true;
+
+    /// Button activated by the back action
+    const BACK_BUTTON: Option<Self::MenuButton> = None;
+
+    /// Button highlighted by the back action, without activating it
+    const BACK_HIGHLIGHT: Option<Self::MenuButton> = None;
 
     /// Button colours
     const BUTTON_COLOUR: Color = Color::srgb(0.15, 0.15, 0.2);
     const BUTTON_SELECTED_COLOUR: Color = Color::srgb(0.30, 0.30, 0.45);
+    const BUTTON_DISABLED_COLOUR: Color = Color::srgb(0.10, 0.10, 0.12);
+
+    /// Handle a selected button
+    fn select(commands: &mut Commands, button: Self::MenuButton);
 
     /// Setup the menu
     fn setup(mut commands: Commands, mut selection: ResMut<MenuSelection>) {
@@ -45,6 +210,11 @@ pub trait Menu {
         // Spawn the menu node
         let mut menu: Entity = Self::spawn_menu(&mut commands);
 
+        // Spawn the title
+        if let Some(title) = Self::TITLE {
+            Self::spawn_title(&mut commands, &mut menu, title);
+        }
+
         // Spawn the buttons
         for button in <Self as Menu>::MenuButton::values() {
             Self::spawn_button(&mut commands, &mut menu, button);
@@ -83,6 +253,30 @@ pub trait Menu {
             .id()
     }
 
+    /// Spawn a title node
+    fn spawn_title(commands: &mut Commands, menu: &mut Entity, title: &str) {
+        // Title attributes
+        const TITLE_MARGIN: Val = Val::Px(24.0);
+        const TITLE_FONT_SIZE: f32 = 48.0;
+
+        // Build the title
+        let title_contents: (Text, TextFont, Node) = (
+            Text::new(title),
+            TextFont {
+                font_size: TITLE_FONT_SIZE,
+                ..default()
+            },
+            Node {
+                margin: UiRect::bottom(TITLE_MARGIN),
+                ..default()
+            },
+        );
+
+        commands.entity(*menu).with_children(|parent| {
+            parent.spawn(title_contents);
+        });
+    }
+
     /// Spawn a button node
     fn spawn_button(commands: &mut Commands, menu: &mut Entity, button_type: Self::MenuButton) {
         // Button attributes
@@ -125,35 +319,107 @@ pub trait Menu {
         });
     }
 
-    /// Handle keyboard input
-    fn keyboard_input(
-        keys: Res<ButtonInput<KeyCode>>,
-        selection: ResMut<MenuSelection>,
-        next_state: ResMut<NextState<Self::State>>,
-        exit: EventWriter<AppExit>,
-    ) where
-        Self::State: FreelyMutableState;
+    /// Handle held navigation actions, repeating after an initial delay
+    fn navigate(
+        actions: Res<ButtonInput<Action>>,
+        time: Res<Time>,
+        mut repeat: ResMut<MenuRepeat>,
+        mut selection: ResMut<MenuSelection>,
+        disabled: Query<&Self::MenuButton, With<DisabledButton>>,
+    ) {
+        // Get the button bounds
+        let count: usize = <Self as Menu>::MenuButton::values().len();
+        let disabled: Vec<usize> = disabled.iter().map(ButtonIndex::index).collect();
+
+        match repeat.update(&actions, time.delta_secs()) {
+            // Move up
+            Some(Action::MoveUp) => selection.step(false, count, Self::WRAP, &disabled),
+            // Move down
+            Some(Action::MoveDown) => selection.step(true, count, Self::WRAP, &disabled),
+            // Move off a button that has been disabled
+            _ if disabled.contains(&selection.index()) => {
+                selection.step(true, count, true, &disabled)
+            }
+            _ => {}
+        }
+    }
+
+    /// Handle action input
+    fn action_input(
+        actions: Res<ButtonInput<Action>>,
+        mut selection: ResMut<MenuSelection>,
+        disabled: Query<&Self::MenuButton, With<DisabledButton>>,
+        mut commands: Commands,
+    ) {
+        // Select
+        if actions.just_pressed(Action::Confirm) {
+            // Determine the selected button
+            <Self as Menu>::MenuButton::from_index(selection.index())
+                // Ignore disabled buttons
+                .map(|button| {
+                    if !disabled.iter().any(|disabled| *disabled == button) {
+                        commands.send_event(PlaySfx(SoundEffect::MenuConfirm));
+                        Self::select(&mut commands, button);
+                    }
+                })
+                // Errors logged automatically within from_index
+                .ok();
+        }
+        // Back
+        else if actions.just_pressed(Action::Back) {
+            // Activate the back button
+            if let Some(button) = Self::BACK_BUTTON {
+                commands.send_event(PlaySfx(SoundEffect::MenuConfirm));
+                Self::select(&mut commands, button);
+            }
+            // Move the selection to the highlighted button
+            else if let Some(button) = Self::BACK_HIGHLIGHT {
+                selection.set_index(button.index());
+            }
+        }
+    }
 
     /// Handle mouse input
     fn mouse_input(
-        interactions: Query<(&Interaction, &Self::MenuButton), Changed<Interaction>>,
-        selection: ResMut<MenuSelection>,
-        next_state: ResMut<NextState<Self::State>>,
-        exit: EventWriter<AppExit>,
-    ) where
-        Self::State: FreelyMutableState;
+        mut interactions: Query<(Entity, &Interaction, &Self::MenuButton), Changed<Interaction>>,
+        disabled: Query<(), With<DisabledButton>>,
+        mut selection: ResMut<MenuSelection>,
+        mut commands: Commands,
+    ) {
+        for (entity, interaction, button) in &mut interactions {
+            // Ignore disabled buttons
+            if disabled.contains(entity) {
+                continue;
+            }
+
+            // Check the mouse interaction type
+            match *interaction {
+                Interaction::Pressed => {
+                    commands.send_event(PlaySfx(SoundEffect::MenuConfirm));
+                    Self::select(&mut commands, button.clone());
+                }
+                Interaction::Hovered => {
+                    // Set the selection index
+                    selection.set_index(button.index());
+                }
+                Interaction::None => {}
+            }
+        }
+    }
 
     /// Update the visuals for the menu buttons
     fn update_visuals(
         selection: Res<MenuSelection>,
-        mut nodes: Query<(&Self::MenuButton, &mut BackgroundColor)>,
+        mut nodes: Query<(&Self::MenuButton, &mut BackgroundColor, Has<DisabledButton>)>,
     ) {
-        for (button, mut background) in &mut nodes {
+        for (button, mut background, disabled) in &mut nodes {
             // Check if the button is selected
             let is_selected: bool = selection.index() == button.index();
 
             // Set the background color
-            background.0 = if is_selected {
+            background.0 = if disabled {
+                Self::BUTTON_DISABLED_COLOUR
+            } else if is_selected {
                 Self::BUTTON_SELECTED_COLOUR
             } else {
                 Self::BUTTON_COLOUR
@@ -161,3 +427,129 @@ pub trait Menu {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn held_navigation_waits_then_repeats() {
+        let mut actions = ButtonInput::<Action>::default();
+        let mut repeat = MenuRepeat::default();
+
+        // A fresh press moves straight away
+        actions.press(Action::MoveDown);
+        assert_eq!(repeat.update(&actions, 0.0), Some(Action::MoveDown));
+        actions.clear();
+
+        // Held, it waits for the initial delay
+        assert_eq!(repeat.update(&actions, 0.3), None);
+        assert_eq!(repeat.update(&actions, 0.1), Some(Action::MoveDown));
+
+        // Then repeats at the interval
+        assert_eq!(repeat.update(&actions, 0.05), None);
+        assert_eq!(repeat.update(&actions, 0.05), Some(Action::MoveDown));
+        assert_eq!(repeat.update(&actions, 0.1), Some(Action::MoveDown));
+    }
+
+    #[test]
+    fn released_navigation_stops_repeating() {
+        let mut actions = ButtonInput::<Action>::default();
+        let mut repeat = MenuRepeat::default();
+        actions.press(Action::MoveUp);
+        assert_eq!(repeat.update(&actions, 0.0), Some(Action::MoveUp));
+        actions.clear();
+
+        // Released before the delay runs out
+        actions.release(Action::MoveUp);
+        assert_eq!(repeat.update(&actions, 1.0), None);
+
+        // Pressing again moves straight away, and restarts the delay
+        actions.clear();
+        actions.press(Action::MoveUp);
+        assert_eq!(repeat.update(&actions, 0.0), Some(Action::MoveUp));
+        actions.clear();
+        assert_eq!(repeat.update(&actions, 0.3), None);
+    }
+
+    #[test]
+    fn selection_wraps_around_the_ends() {
+        let mut selection = MenuSelection::default();
+        selection.step(false, 3, true, &[]);
+        assert_eq!(selection.index(), 2);
+        selection.step(true, 3, true, &[]);
+        assert_eq!(selection.index(), 0);
+
+        // Without wrapping it stops at the ends
+        selection.step(false, 3, false, &[]);
+        assert_eq!(selection.index(), 0);
+        selection.set_index(2);
+        selection.step(true, 3, false, &[]);
+        assert_eq!(selection.index(), 2);
+    }
+
+    #[test]
+    fn selection_skips_disabled_buttons() {
+        let mut selection = MenuSelection::default();
+        selection.step(true, 4, true, &[1, 2]);
+        assert_eq!(selection.index(), 3);
+        selection.step(true, 4, true, &[0]);
+        assert_eq!(selection.index(), 1);
+
+        // Stays put at the end when only disabled buttons are left
+        selection.set_index(1);
+        selection.step(true, 3, false, &[2]);
+        assert_eq!(selection.index(), 1);
+
+        // Stays put when every other button is disabled
+        selection.step(true, 3, true, &[0, 2]);
+        assert_eq!(selection.index(), 1);
+    }
+
+    #[test]
+    fn rebinding_ignores_the_key_that_started_it() {
+        let mut keyboard_input = ButtonInput::<KeyCode>::default();
+        keyboard_input.press(KeyCode::Enter);
+        let mut rebinding = Rebinding::new(Action::MoveUp);
+
+        // The confirm key is still held, then released
+        assert_eq!(rebinding.capture(&keyboard_input), None);
+        keyboard_input.clear();
+        keyboard_input.release(KeyCode::Enter);
+        assert_eq!(rebinding.capture(&keyboard_input), None);
+
+        // The next key is bound once it is released
+        keyboard_input.clear();
+        keyboard_input.press(KeyCode::KeyQ);
+        assert_eq!(rebinding.capture(&keyboard_input), None);
+        keyboard_input.clear();
+        keyboard_input.release(KeyCode::KeyQ);
+        assert_eq!(
+            rebinding.capture(&keyboard_input),
+            Some((Action::MoveUp, KeyCode::KeyQ))
+        );
+        assert_eq!(rebinding.get(), None);
+    }
+
+    #[test]
+    fn rebinding_accepts_the_starting_key_pressed_again() {
+        let mut keyboard_input = ButtonInput::<KeyCode>::default();
+        keyboard_input.press(KeyCode::Enter);
+        let mut rebinding = Rebinding::new(Action::Confirm);
+        assert_eq!(rebinding.capture(&keyboard_input), None);
+
+        // Release and press the confirm key again
+        keyboard_input.clear();
+        keyboard_input.release(KeyCode::Enter);
+        assert_eq!(rebinding.capture(&keyboard_input), None);
+        keyboard_input.clear();
+        keyboard_input.press(KeyCode::Enter);
+        assert_eq!(rebinding.capture(&keyboard_input), None);
+        keyboard_input.clear();
+        keyboard_input.release(KeyCode::Enter);
+        assert_eq!(
+            rebinding.capture(&keyboard_input),
+            Some((Action::Confirm, KeyCode::Enter))
+        );
+    }
+}
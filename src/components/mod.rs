@@ -1,3 +1,11 @@
+pub mod audio;
 pub mod button;
+pub mod camera;
+pub mod collision;
+pub mod hud;
+pub mod level;
 pub mod menu;
+pub mod movement;
+pub mod obstacle;
+pub mod pickup;
 pub mod player;
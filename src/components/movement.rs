@@ -0,0 +1,152 @@
+use bevy::prelude::*;
+
+// Modules
+use crate::tuning::PlayerTuning;
+
+/// Velocity component, in world units per second
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Velocity {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Default for Velocity {
+    fn default() -> Self {
+        Self { x: 0.0, y: 0.0 }
+    }
+}
+
+impl Velocity {
+    /// Get the velocity as a vector
+    pub fn get(&self) -> Vec2 {
+        Vec2::new(self.x, self.y)
+    }
+
+    /// Set the velocity from a vector
+    pub fn set(&mut self, velocity: Vec2) {
+        self.x = velocity.x;
+        self.y = velocity.y;
+    }
+}
+
+/// Acceleration component, in world units per second squared
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Acceleration {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Default for Acceleration {
+    fn default() -> Self {
+        Self { x: 0.0, y: 0.0 }
+    }
+}
+
+impl Acceleration {
+    /// Get the acceleration as a vector
+    pub fn get(&self) -> Vec2 {
+        Vec2::new(self.x, self.y)
+    }
+
+    /// Set the acceleration from a vector
+    pub fn set(&mut self, acceleration: Vec2) {
+        self.x = acceleration.x;
+        self.y = acceleration.y;
+    }
+}
+
+/// Movement tuning for an entity with velocity
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Movement {
+    /// Top speed, in world units per second
+    pub max_speed: f32,
+    /// Acceleration applied at full input, in world units per second squared
+    pub acceleration: f32,
+    /// Deceleration applied while coasting, in world units per second squared
+    pub friction: f32,
+    /// Velocity lost per second in proportion to speed
+    pub drag: f32,
+    /// Scales the top speed and acceleration, for temporary boosts
+    pub speed_multiplier: f32,
+}
+
+impl Default for Movement {
+    fn default() -> Self {
+        Self::from(&PlayerTuning::default())
+    }
+}
+
+impl From<&PlayerTuning> for Movement {
+    fn from(tuning: &PlayerTuning) -> Self {
+        Self {
+            max_speed: tuning.max_speed,
+            acceleration: tuning.acceleration,
+            friction: tuning.friction,
+            drag: tuning.drag,
+            speed_multiplier: 1.0,
+        }
+    }
+}
+
+/// Rendered position of an entity simulated in fixed timesteps, smoothed between steps
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct Interpolated {
+    /// Simulated position before the latest fixed timestep
+    previous: Option<Vec3>,
+    /// Simulated position after the latest fixed timestep
+    current: Option<Vec3>,
+}
+
+impl Interpolated {
+    /// Put back the simulated position, replacing the rendered one
+    pub fn restore(&self, translation: &mut Vec3) {
+        if let Some(current) = self.current {
+            *translation = current;
+        }
+    }
+
+    /// Move straight to a new simulated position, without smoothing from the old one
+    pub fn teleport(&mut self, translation: Vec3) {
+        self.previous = Some(translation);
+        self.current = Some(translation);
+    }
+
+    /// Record the simulated position before a fixed timestep
+    pub fn store(&mut self, translation: Vec3) {
+        self.previous = Some(translation);
+    }
+
+    /// Record the simulated position, then render part way from the previous one
+    pub fn apply(&mut self, translation: &mut Vec3, fraction: f32) {
+        self.current = Some(*translation);
+        if let Some(previous) = self.previous {
+            *translation = previous.lerp(*translation, fraction);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn teleport_skips_smoothing() {
+        let mut interpolated = Interpolated::default();
+        let mut translation = Vec3::ZERO;
+
+        // Simulate a step to the right, rendered half way
+        interpolated.store(translation);
+        translation.x = 1.0;
+        interpolated.apply(&mut translation, 0.5);
+        assert_eq!(translation.x, 0.5);
+
+        // Teleport between timesteps, which isn't put back or smoothed
+        interpolated.restore(&mut translation);
+        translation = Vec3::new(10.0, 0.0, 0.0);
+        interpolated.teleport(translation);
+        interpolated.restore(&mut translation);
+        assert_eq!(translation.x, 10.0);
+        interpolated.apply(&mut translation, 0.5);
+        assert_eq!(translation.x, 10.0);
+    }
+}
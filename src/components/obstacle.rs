@@ -0,0 +1,70 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Marker component for static walls
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct Wall;
+
+/// Hurts the player on contact
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Hazard {
+    pub damage: u32,
+}
+
+impl Default for Hazard {
+    fn default() -> Self {
+        Self { damage: 1 }
+    }
+}
+
+/// Moves an entity back and forth between waypoints
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Patrol {
+    points: Vec<Vec2>,
+    /// Speed at level 1, in world units per second
+    pub speed: f32,
+    target: usize,
+}
+
+impl Patrol {
+    /// Create a patrol that loops through the waypoints in order
+    pub fn new(points: Vec<Vec2>, speed: f32) -> Self {
+        Self {
+            points,
+            speed,
+            target: 0,
+        }
+    }
+
+    /// Get the waypoint the entity is heading towards
+    pub fn target(&self) -> Option<Vec2> {
+        self.points.get(self.target).copied()
+    }
+
+    /// Head towards the next waypoint, looping back to the first
+    pub fn advance(&mut self) {
+        self.target = (self.target + 1) % self.points.len().max(1);
+    }
+}
+
+/// Emits hazards on a schedule, measured in game time
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct HazardSpawner {
+    /// Seconds between hazards at level 1
+    pub interval: f32,
+    /// Hazard velocity at level 1, in world units per second
+    pub velocity: Vec2,
+    /// Seconds each hazard lasts before it's despawned
+    pub lifetime: f32,
+    /// Largest random turn of each hazard's velocity either way, in radians
+    #[serde(default)]
+    pub spread: f32,
+    /// Game time of the next hazard
+    pub next_spawn: f32,
+}
+
+/// Despawns an entity once the game time passes it
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Lifetime {
+    pub expires: f32,
+}
@@ -0,0 +1,68 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Collectibles and power-ups, picked up when the player touches them
+#[derive(
+    Component, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+pub enum Pickup {
+    Coin,
+    Gem,
+    SpeedBoost,
+    Shield,
+    ScoreMultiplier,
+}
+
+impl Pickup {
+    /// Get the points scored for picking it up
+    pub fn points(&self) -> u32 {
+        match self {
+            Pickup::Coin => 10,
+            Pickup::Gem => 50,
+            Pickup::SpeedBoost | Pickup::Shield | Pickup::ScoreMultiplier => 0,
+        }
+    }
+
+    /// Get how long the power-up lasts, in seconds of game time
+    pub fn duration(&self) -> Option<f32> {
+        match self {
+            Pickup::Coin | Pickup::Gem => None,
+            Pickup::SpeedBoost => Some(5.0),
+            Pickup::Shield => Some(5.0),
+            Pickup::ScoreMultiplier => Some(10.0),
+        }
+    }
+
+    /// Get the colour it's drawn in
+    pub fn colour(&self) -> Color {
+        match self {
+            Pickup::Coin => Color::srgb(0.95, 0.8, 0.2),
+            Pickup::Gem => Color::srgb(0.3, 0.85, 0.95),
+            Pickup::SpeedBoost => Color::srgb(0.3, 0.9, 0.3),
+            Pickup::Shield => Color::srgb(0.4, 0.5, 1.0),
+            Pickup::ScoreMultiplier => Color::srgb(0.9, 0.4, 0.9),
+        }
+    }
+}
+
+/// Active power-ups, each with the game time it expires
+#[derive(Component, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PowerUps {
+    expires: BTreeMap<Pickup, f32>,
+}
+
+impl PowerUps {
+    /// Activate a power-up until a game time, extending it if already active
+    pub fn activate(&mut self, power_up: Pickup, until: f32) {
+        let expires: &mut f32 = self.expires.entry(power_up).or_default();
+        *expires = expires.max(until);
+    }
+
+    /// Check whether a power-up is active at a game time
+    pub fn active(&self, power_up: Pickup, seconds: f32) -> bool {
+        self.expires
+            .get(&power_up)
+            .is_some_and(|expires| seconds < *expires)
+    }
+}
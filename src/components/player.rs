@@ -4,15 +4,71 @@ use bevy::prelude::*;
 #[derive(Component)]
 pub struct Player;
 
-/// Velocity component
-#[derive(Component)]
-pub struct Velocity {
-    _x: f32,
-    _y: f32,
+/// Hit points, restored on respawn
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Health {
+    current: u32,
+    max: u32,
 }
 
-impl Default for Velocity {
-    fn default() -> Self {
-        Self { _x: 0.0, _y: 0.0 }
+impl Health {
+    /// Create full health
+    pub fn new(max: u32) -> Self {
+        Self { current: max, max }
+    }
+
+    /// Getter for the current health
+    pub fn current(&self) -> u32 {
+        self.current
+    }
+
+    /// Take damage, returning whether the health ran out
+    pub fn damage(&mut self, amount: u32) -> bool {
+        self.current = self.current.saturating_sub(amount);
+        self.current == 0
+    }
+
+    /// Set the current health, up to the maximum
+    pub fn set_current(&mut self, current: u32) {
+        self.current = current.min(self.max);
+    }
+
+    /// Restore full health
+    pub fn restore(&mut self) {
+        self.current = self.max;
     }
 }
+
+/// Remaining lives, including the current one
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Lives(pub u32);
+
+impl Lives {
+    /// Getter for the remaining lives
+    pub fn get(&self) -> u32 {
+        self.0
+    }
+
+    /// Lose a life, returning the lives left
+    pub fn lose(&mut self) -> u32 {
+        self.0 = self.0.saturating_sub(1);
+        self.0
+    }
+}
+
+/// Ignores damage until the game time passes it
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct Invulnerable {
+    pub until: f32,
+}
+
+impl Invulnerable {
+    /// Check whether damage is ignored at a game time
+    pub fn active(&self, seconds: f32) -> bool {
+        seconds < self.until
+    }
+}
+
+/// Where the player returns to after losing a life
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct SpawnPoint(pub Vec2);
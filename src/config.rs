@@ -0,0 +1,110 @@
+use bevy::asset::io::Reader;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+// Modules
+use crate::error::{Error, ErrorLevel};
+
+/// Directory name for the game's config files
+const CONFIG_DIR_NAME: &str = "dorian";
+
+/// Get the path for a config file in the platform config directory
+pub fn config_path(file_name: &str) -> Result<PathBuf, Error> {
+    dirs::config_dir()
+        .map(|dir| dir.join(CONFIG_DIR_NAME).join(file_name))
+        .ok_or_else(|| {
+            Error::new(
+                ErrorLevel::Warning,
+                "Unable to determine the platform config directory",
+                None,
+            )
+        })
+}
+
+/// Load a RON config file, returning `None` when it doesn't exist yet
+pub fn load_ron<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, Error> {
+    // Read the file
+    let contents: String = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(err) => {
+            return Err(Error::new(
+                ErrorLevel::Warning,
+                &format!("Unable to read config file {}", path.display()),
+                Some(Box::new(err)),
+            ))
+        }
+    };
+
+    // Parse the file
+    ron::from_str(&contents).map(Some).map_err(|err| {
+        Error::new(
+            ErrorLevel::Warning,
+            &format!("Unable to parse config file {}", path.display()),
+            Some(Box::new(err)),
+        )
+    })
+}
+
+/// Save a RON config file, creating its directory if needed
+pub fn save_ron<T: Serialize>(path: &Path, value: &T) -> Result<(), Error> {
+    // Serialise the value
+    let contents: String = ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())
+        .map_err(|err| {
+            Error::new(
+                ErrorLevel::Warning,
+                &format!("Unable to serialise config file {}", path.display()),
+                Some(Box::new(err)),
+            )
+        })?;
+
+    // Create the config directory
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|err| {
+            Error::new(
+                ErrorLevel::Warning,
+                &format!("Unable to create config directory {}", dir.display()),
+                Some(Box::new(err)),
+            )
+        })?;
+    }
+
+    // Write the file
+    fs::write(path, contents).map_err(|err| {
+        Error::new(
+            ErrorLevel::Warning,
+            &format!("Unable to write config file {}", path.display()),
+            Some(Box::new(err)),
+        )
+    })
+}
+
+/// Read and parse a RON asset, naming the kind of asset in any error
+pub async fn read_ron_asset<T: DeserializeOwned>(
+    reader: &mut dyn Reader,
+    path: &Path,
+    kind: &str,
+) -> Result<T, Error> {
+    // Read the file
+    let mut bytes: Vec<u8> = Vec::new();
+    reader.read_to_end(&mut bytes).await.map_err(|err| {
+        Error::new(
+            ErrorLevel::Error,
+            &format!("Unable to read {kind} file {}", path.display()),
+            Some(Box::new(err)),
+        )
+    })?;
+
+    // Parse the file
+    ron::de::from_bytes(&bytes).map_err(|err| {
+        Error::new(
+            ErrorLevel::Error,
+            &format!("Unable to parse {kind} file {}", path.display()),
+            Some(Box::new(err)),
+        )
+    })
+}
@@ -0,0 +1,86 @@
+use bevy::{math::curve::EaseFunction, prelude::*};
+
+// Modules
+use crate::components::{camera::CameraPath, pickup::Pickup};
+
+/// Points scored during a run
+#[derive(Event, Debug, Clone, Copy)]
+pub struct ScoreEvent {
+    pub points: u32,
+}
+
+/// The run advanced to a new level
+#[derive(Event, Debug, Clone, Copy)]
+pub struct LevelUp {
+    pub level: u32,
+}
+
+/// Sound effects
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoundEffect {
+    MenuHover,
+    MenuConfirm,
+}
+
+impl SoundEffect {
+    /// All sound effects
+    pub const VALUES: [Self; 2] = [Self::MenuHover, Self::MenuConfirm];
+
+    /// Get the asset path for a sound effect
+    pub fn path(&self) -> &'static str {
+        match self {
+            SoundEffect::MenuHover => "audio/menu_hover.ogg",
+            SoundEffect::MenuConfirm => "audio/menu_confirm.ogg",
+        }
+    }
+}
+
+/// Play a sound effect on the SFX channel
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PlaySfx(pub SoundEffect);
+
+/// Two colliders started overlapping
+#[derive(Event, Debug, Clone, Copy)]
+pub struct CollisionStart {
+    pub a: Entity,
+    pub b: Entity,
+}
+
+/// Two colliders are still overlapping
+#[derive(Event, Debug, Clone, Copy)]
+pub struct CollisionStay {
+    pub a: Entity,
+    pub b: Entity,
+}
+
+/// Damage dealt to an entity
+#[derive(Event, Debug, Clone, Copy)]
+pub struct Damage {
+    pub target: Entity,
+    pub amount: u32,
+}
+
+/// The player picked up a collectible or power-up
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PickupCollected {
+    pub player: Entity,
+    pub pickup: Pickup,
+}
+
+/// Shake the camera, adding trauma from 0 to 1
+#[derive(Event, Debug, Clone, Copy)]
+pub struct CameraShake {
+    pub trauma: f32,
+}
+
+/// Ease the camera's field of view to an angle in radians, over seconds of game time
+#[derive(Event, Debug, Clone, Copy)]
+pub struct CameraZoom {
+    pub fov: f32,
+    pub duration: f32,
+    pub ease: EaseFunction,
+}
+
+/// Play a scripted camera move
+#[derive(Event, Debug, Clone)]
+pub struct CameraMove(pub CameraPath);
@@ -0,0 +1,145 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+// Modules
+use crate::{
+    error::{Error, ErrorLevel},
+    resources::Action,
+    systems::time::FIXED_TIMESTEP_HZ,
+};
+
+/// Settings for a run without a window or renderer
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct HeadlessRun {
+    /// When to stop the run
+    pub limit: RunLimit,
+    /// Input script to drive the player with, if any
+    pub input: Option<PathBuf>,
+    /// File to save the run's replay to, if it should be recorded
+    pub record: Option<PathBuf>,
+}
+
+/// How long a headless run lasts, unless the game ends first
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RunLimit {
+    /// Number of fixed timesteps
+    Ticks(u64),
+    /// Seconds of game time
+    Seconds(f32),
+}
+
+impl HeadlessRun {
+    /// Run for a number of fixed timesteps
+    pub fn ticks(ticks: u64) -> Self {
+        Self {
+            limit: RunLimit::Ticks(ticks),
+            input: None,
+            record: None,
+        }
+    }
+
+    /// Run for a number of seconds of game time
+    pub fn seconds(seconds: f32) -> Self {
+        Self {
+            limit: RunLimit::Seconds(seconds),
+            input: None,
+            record: None,
+        }
+    }
+
+    /// Drive the player with an input script
+    pub fn with_input(mut self, path: impl Into<PathBuf>) -> Self {
+        self.input = Some(path.into());
+        self
+    }
+
+    /// Record the run, saving its replay to a file
+    pub fn with_recording(mut self, path: impl Into<PathBuf>) -> Self {
+        self.record = Some(path.into());
+        self
+    }
+}
+
+impl RunLimit {
+    /// Get the limit as a number of fixed timesteps
+    pub fn ticks(&self) -> u64 {
+        match *self {
+            Self::Ticks(ticks) => ticks,
+            Self::Seconds(seconds) => (seconds as f64 * FIXED_TIMESTEP_HZ).ceil() as u64,
+        }
+    }
+}
+
+/// Actions to hold from each fixed timestep of a run, loaded from a RON file
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InputScript {
+    pub steps: Vec<ScriptStep>,
+}
+
+/// Actions held from a fixed timestep, until the next step
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScriptStep {
+    /// Fixed timestep to start from, counted from the start of the run
+    pub tick: u64,
+    /// Actions to hold
+    #[serde(default)]
+    pub held: Vec<Action>,
+    /// Analog stick position, overriding the held movement actions when pushed
+    #[serde(default, skip_serializing_if = "is_centred")]
+    pub stick: Vec2,
+}
+
+/// Check whether a stick is centred, to leave it out of saved steps
+fn is_centred(stick: &Vec2) -> bool {
+    *stick == Vec2::ZERO
+}
+
+impl InputScript {
+    /// Load an input script, with its steps in tick order
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        // Read the file
+        let contents: String = fs::read_to_string(path).map_err(|err| {
+            Error::new(
+                ErrorLevel::Error,
+                &format!("Unable to read input script {}", path.display()),
+                Some(Box::new(err)),
+            )
+        })?;
+
+        // Parse the script
+        let mut script: Self = ron::from_str(&contents).map_err(|err| {
+            Error::new(
+                ErrorLevel::Error,
+                &format!("Unable to parse input script {}", path.display()),
+                Some(Box::new(err)),
+            )
+        })?;
+        script.steps.sort_by_key(|step| step.tick);
+
+        Ok(script)
+    }
+
+    /// Get the step in effect at a fixed timestep
+    pub fn step(&self, tick: u64) -> Option<&ScriptStep> {
+        let next: usize = self.steps.partition_point(|step| step.tick <= tick);
+        next.checked_sub(1).map(|index| &self.steps[index])
+    }
+
+    /// Record the input at a fixed timestep, adding a step only when it changes
+    pub fn record(&mut self, tick: u64, mut held: Vec<Action>, stick: Vec2) {
+        held.sort();
+        if self
+            .steps
+            .last()
+            .is_some_and(|step| step.held == held && step.stick == stick)
+        {
+            return;
+        }
+
+        self.steps.push(ScriptStep { tick, held, stick });
+    }
+}
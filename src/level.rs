@@ -0,0 +1,466 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+// Modules
+use crate::{
+    components::{
+        camera::{CameraMode, CameraPath},
+        pickup::Pickup,
+    },
+    config::read_ron_asset,
+    error::{Error, ErrorLevel},
+};
+
+/// Level 1's file, built in for when the level assets can't be loaded
+const BUILT_IN_LEVEL: &str = include_str!("../assets/levels/1.level.ron");
+
+/// Level layout, loaded from a `.level.ron` asset
+#[derive(Asset, TypePath, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LevelDefinition {
+    pub name: String,
+    pub spawn_point: Vec2,
+    #[serde(default)]
+    pub extents: LevelExtents,
+    #[serde(default)]
+    pub camera: LevelCamera,
+    #[serde(default)]
+    pub lighting: LevelLighting,
+    #[serde(default)]
+    pub walls: Vec<WallDefinition>,
+    #[serde(default)]
+    pub patrols: Vec<PatrolDefinition>,
+    #[serde(default)]
+    pub spawners: Vec<SpawnerDefinition>,
+    #[serde(default)]
+    pub pickups: Vec<PickupDefinition>,
+}
+
+/// Area the player and the camera's view are kept within
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LevelExtents {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+/// Camera placement for a level, how it follows the player, and its intro
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LevelCamera {
+    pub position: Vec3,
+    pub look_at: Vec3,
+    #[serde(default)]
+    pub mode: CameraMode,
+    /// Vertical field of view in degrees, eased to as the level starts
+    #[serde(default = "LevelCamera::fov_default")]
+    pub fov: f32,
+    /// Scripted camera move played as the level starts
+    #[serde(default)]
+    pub intro: Option<CameraPath>,
+}
+
+/// Lighting for a level
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LevelLighting {
+    pub ambient_brightness: f32,
+    pub illuminance: f32,
+}
+
+/// A static wall
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WallDefinition {
+    pub position: Vec2,
+    pub size: Vec2,
+}
+
+/// A hazard patrolling between waypoints, starting at the first
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PatrolDefinition {
+    pub points: Vec<Vec2>,
+    pub speed: f32,
+}
+
+/// A hazard spawner, with its timings in seconds at level 1
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SpawnerDefinition {
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub interval: f32,
+    pub lifetime: f32,
+    /// Largest random turn of each hazard's velocity either way, in radians
+    #[serde(default)]
+    pub spread: f32,
+}
+
+/// A pickup, spawned in turn with the others in the level
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PickupDefinition {
+    pub kind: Pickup,
+    pub position: Vec2,
+}
+
+impl Default for LevelExtents {
+    /// The area seen by the default camera in a 16:9 window
+    fn default() -> Self {
+        Self {
+            min: Vec2::new(-5.6, -3.15),
+            max: Vec2::new(5.6, 3.15),
+        }
+    }
+}
+
+impl LevelExtents {
+    /// Get the extents as a rectangle
+    pub fn rect(&self) -> Rect {
+        Rect::from_corners(self.min, self.max)
+    }
+}
+
+impl Default for LevelCamera {
+    fn default() -> Self {
+        Self {
+            position: Vec3::new(0.0, 3.0, 7.0),
+            look_at: Vec3::ZERO,
+            mode: CameraMode::default(),
+            fov: Self::fov_default(),
+            intro: None,
+        }
+    }
+}
+
+impl LevelCamera {
+    /// Field of view for levels that don't set their own, matching Bevy's default
+    fn fov_default() -> f32 {
+        PerspectiveProjection::default().fov.to_degrees()
+    }
+}
+
+impl Default for LevelLighting {
+    fn default() -> Self {
+        Self {
+            ambient_brightness: 75.0,
+            illuminance: 10000.0,
+        }
+    }
+}
+
+impl Default for LevelDefinition {
+    /// Built-in level, used when the level assets can't be loaded
+    fn default() -> Self {
+        ron::from_str(BUILT_IN_LEVEL)
+            .map_err(|err| {
+                Error::new(
+                    ErrorLevel::Error,
+                    "Unable to parse the built-in level",
+                    Some(Box::new(err)),
+                )
+            })
+            // Errors logged automatically within Error::new
+            .unwrap_or_else(|_| Self::empty())
+    }
+}
+
+impl LevelDefinition {
+    /// Asset folder holding the level files
+    pub const DIR: &'static str = "levels";
+
+    /// File extension for level assets
+    pub const EXTENSION: &'static str = "level.ron";
+
+    /// Open level with nothing in it, if the built-in level can't be parsed
+    fn empty() -> Self {
+        Self {
+            name: "Empty".to_string(),
+            spawn_point: Vec2::ZERO,
+            extents: LevelExtents::default(),
+            camera: LevelCamera::default(),
+            lighting: LevelLighting::default(),
+            walls: Vec::new(),
+            patrols: Vec::new(),
+            spawners: Vec::new(),
+            pickups: Vec::new(),
+        }
+    }
+
+    /// Get the asset path for a level number
+    pub fn path(level: u32) -> String {
+        format!("{}/{level}.{}", Self::DIR, Self::EXTENSION)
+    }
+
+    /// Check the level is playable, describing the first problem found
+    pub fn validate(&self) -> Result<(), String> {
+        // Placement
+        if !self.spawn_point.is_finite() {
+            return Err("the spawn point must be a finite position".to_string());
+        }
+        if !self.camera.position.is_finite() || !self.camera.look_at.is_finite() {
+            return Err("the camera position and target must be finite".to_string());
+        }
+        if self.camera.position == self.camera.look_at {
+            return Err("the camera can't look at its own position".to_string());
+        }
+        match self.camera.mode {
+            CameraMode::Fixed => {}
+            CameraMode::Follow { dead_zone } => {
+                if !non_negative(dead_zone.x) || !non_negative(dead_zone.y) {
+                    return Err("the camera's dead zone can't be negative".to_string());
+                }
+            }
+            CameraMode::SmoothFollow { smooth_time } => {
+                if !positive(smooth_time) {
+                    return Err("the camera's smooth time must be positive".to_string());
+                }
+            }
+            CameraMode::LookAhead {
+                distance,
+                smooth_time,
+            } => {
+                if !non_negative(distance) || !positive(smooth_time) {
+                    return Err(
+                        "the camera's look-ahead distance can't be negative, and its \
+                        smooth time must be positive"
+                            .to_string(),
+                    );
+                }
+            }
+        }
+
+        if !positive(self.camera.fov) || self.camera.fov >= 180.0 {
+            return Err("the camera's field of view must be between 0 and 180 degrees".to_string());
+        }
+        if let Some(intro) = &self.camera.intro {
+            if !non_negative(intro.settle) {
+                return Err("the camera intro's settle time can't be negative".to_string());
+            }
+            for (index, waypoint) in intro.waypoints.iter().enumerate() {
+                if !waypoint.position.is_finite()
+                    || !waypoint.look_at.is_finite()
+                    || waypoint.position == waypoint.look_at
+                    || !non_negative(waypoint.duration)
+                {
+                    return Err(format!(
+                        "camera intro waypoint {index} must look away from a finite position, \
+                        with a duration that isn't negative"
+                    ));
+                }
+            }
+        }
+
+        // Extents
+        if !self.extents.min.is_finite()
+            || !self.extents.max.is_finite()
+            || self.extents.min.cmpge(self.extents.max).any()
+        {
+            return Err("the extents must be finite, with max above min".to_string());
+        }
+        if !self.extents.rect().contains(self.spawn_point) {
+            return Err("the spawn point is outside the extents".to_string());
+        }
+
+        // Lighting
+        if !non_negative(self.lighting.ambient_brightness)
+            || !non_negative(self.lighting.illuminance)
+        {
+            return Err("the lighting brightness and illuminance can't be negative".to_string());
+        }
+
+        // Walls
+        for (index, wall) in self.walls.iter().enumerate() {
+            if !wall.position.is_finite() || !positive(wall.size.x) || !positive(wall.size.y) {
+                return Err(format!(
+                    "wall {index} must have a finite position and a positive size"
+                ));
+            }
+            if Rect::from_center_size(wall.position, wall.size).contains(self.spawn_point) {
+                return Err(format!("the spawn point is inside wall {index}"));
+            }
+        }
+
+        // Patrols
+        for (index, patrol) in self.patrols.iter().enumerate() {
+            if patrol.points.len() < 2 {
+                return Err(format!("patrol {index} needs at least 2 waypoints"));
+            }
+            if !patrol.points.iter().all(|point| point.is_finite()) {
+                return Err(format!("patrol {index} has a waypoint that isn't finite"));
+            }
+            if !positive(patrol.speed) {
+                return Err(format!("patrol {index} must have a positive speed"));
+            }
+        }
+
+        // Spawners
+        for (index, spawner) in self.spawners.iter().enumerate() {
+            if !spawner.position.is_finite() || !spawner.velocity.is_finite() {
+                return Err(format!(
+                    "spawner {index} must have a finite position and velocity"
+                ));
+            }
+            if !positive(spawner.interval) || !positive(spawner.lifetime) {
+                return Err(format!(
+                    "spawner {index} must have a positive interval and lifetime"
+                ));
+            }
+            if !non_negative(spawner.spread) {
+                return Err(format!("spawner {index} can't have a negative spread"));
+            }
+        }
+
+        // Pickups
+        for (index, pickup) in self.pickups.iter().enumerate() {
+            if !pickup.position.is_finite() {
+                return Err(format!("pickup {index} must have a finite position"));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Check a value is finite and above zero
+fn positive(value: f32) -> bool {
+    value.is_finite() && value > 0.0
+}
+
+/// Check a value is finite and not below zero
+fn non_negative(value: f32) -> bool {
+    value.is_finite() && value >= 0.0
+}
+
+/// Loads and validates `.level.ron` assets
+#[derive(Default)]
+pub struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+    type Asset = LevelDefinition;
+    type Settings = ();
+    type Error = Error;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<LevelDefinition, Error> {
+        // Read the level
+        let level: LevelDefinition = read_ron_asset(reader, load_context.path(), "level").await?;
+
+        // Validate the level
+        level.validate().map_err(|problem| {
+            Error::new(
+                ErrorLevel::Error,
+                &format!(
+                    "Invalid level file {}: {problem}",
+                    load_context.path().display()
+                ),
+                None,
+            )
+        })?;
+
+        Ok(level)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &[LevelDefinition::EXTENSION]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    // Modules
+    use super::*;
+
+    /// Spawner that passes validation
+    const SPAWNER: SpawnerDefinition = SpawnerDefinition {
+        position: Vec2::new(4.0, 0.0),
+        velocity: Vec2::new(-1.0, 0.0),
+        interval: 2.0,
+        lifetime: 5.0,
+        spread: 0.0,
+    };
+
+    #[test]
+    fn shipped_levels_are_valid() {
+        assert_eq!(LevelDefinition::default().validate(), Ok(()));
+        assert_eq!(LevelDefinition::empty().validate(), Ok(()));
+
+        for level in 1..=3 {
+            let path: PathBuf = Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("assets")
+                .join(LevelDefinition::path(level));
+            let file: String = std::fs::read_to_string(&path).unwrap_or_default();
+            let definition: Result<LevelDefinition, String> =
+                ron::from_str(&file).map_err(|err| format!("{}: {err}", path.display()));
+            assert_eq!(definition.and_then(|level| level.validate()), Ok(()));
+        }
+    }
+
+    #[test]
+    fn spawn_point_must_be_inside_the_extents() {
+        let mut level = LevelDefinition::empty();
+        level.spawn_point = level.extents.max + Vec2::ONE;
+        assert!(level.validate().is_err());
+
+        // Including on its edge
+        level.spawn_point = level.extents.max;
+        assert_eq!(level.validate(), Ok(()));
+    }
+
+    #[test]
+    fn walls_must_have_a_size() {
+        let sizes = [Vec2::ZERO, Vec2::new(1.0, 0.0), Vec2::new(-1.0, 1.0)];
+        for size in sizes {
+            let mut level = LevelDefinition::empty();
+            level.walls.push(WallDefinition {
+                position: Vec2::new(3.0, 0.0),
+                size,
+            });
+            assert!(level.validate().is_err(), "wall of size {size}");
+        }
+
+        // Nor cover the spawn point
+        let mut level = LevelDefinition::empty();
+        level.walls.push(WallDefinition {
+            position: level.spawn_point,
+            size: Vec2::ONE,
+        });
+        assert!(level.validate().is_err());
+    }
+
+    #[test]
+    fn spawners_must_have_positive_timings() {
+        let mut level = LevelDefinition::empty();
+        level.spawners.push(SPAWNER);
+        assert_eq!(level.validate(), Ok(()));
+
+        let spawners = [
+            SpawnerDefinition {
+                interval: 0.0,
+                ..SPAWNER
+            },
+            SpawnerDefinition {
+                interval: -1.0,
+                ..SPAWNER
+            },
+            SpawnerDefinition {
+                interval: f32::NAN,
+                ..SPAWNER
+            },
+            SpawnerDefinition {
+                lifetime: 0.0,
+                ..SPAWNER
+            },
+            SpawnerDefinition {
+                spread: -0.1,
+                ..SPAWNER
+            },
+        ];
+        for spawner in spawners {
+            level.spawners = vec![spawner];
+            assert!(level.validate().is_err(), "{spawner:?}");
+        }
+    }
+}
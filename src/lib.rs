@@ -1,76 +1,546 @@
-use bevy::prelude::*;
+use bevy::{
+    app::ScheduleRunnerPlugin,
+    audio::AudioPlugin,
+    hierarchy::HierarchyPlugin,
+    input::{InputPlugin, InputSystem},
+    log::LogPlugin,
+    prelude::*,
+    state::app::StatesPlugin,
+    time::TimeUpdateStrategy,
+    transform::TransformPlugin,
+};
+use std::{path::PathBuf, time::Duration};
 
 // Modules
 mod components;
+mod config;
 mod error;
+mod events;
+mod headless;
+mod level;
+mod replay;
 mod resources;
 mod systems;
+mod tuning;
 use crate::{
-    components::menu::{IngameMenu, Menu, MenuSelection, StartMenu},
-    resources::{AppState, PauseState},
+    components::menu::{
+        BindingsMenu, GameOverMenu, IngameMenu, Menu, MenuRepeat, MenuSelection, Rebinding,
+        SettingsMenu, StartMenu,
+    },
+    headless::InputScript,
+    level::{LevelDefinition, LevelLoader},
+    resources::{AppState, PauseState, SettingsState},
     systems::{
-        camera::camera_setup,
+        audio::{
+            audio_check, audio_load, audio_volume, menu_hover_sound, music_change, music_fade,
+            sfx_play,
+        },
+        camera::{
+            camera_effects, camera_effects_reset, camera_events, camera_follow, camera_setup,
+        },
+        collision::{collision_detect, collision_resolve},
+        headless::{headless_finish, script_load},
+        hud::{hud_lives, hud_progress, hud_setup, hud_time, hud_visibility},
+        input::{actions_clear, bindings_load, bindings_save, script_actions, update_actions},
+        level::{
+            level_advance, level_intro, level_load, level_loading, level_reload, level_spawn,
+            level_spawn_point, level_view,
+        },
         menu::pause_toggle,
-        player::{player_animate, player_movement, player_setup},
-        time::tick_game_time,
+        movement::{
+            apply_velocity, interpolation_apply, interpolation_restore, interpolation_store,
+        },
+        obstacle::{hazard_hit, hazard_patrol, hazard_spawn, lifetime_expire},
+        pickup::{pickup_apply, pickup_collect, pickup_spawn, power_up_effects},
+        player::{
+            player_animate, player_bounds, player_damage, player_movement, player_setup,
+            player_tuning,
+        },
+        progression::{level_progression, score_update},
+        replay::{
+            replay_finished, replay_load, replay_pause, replay_record, replay_record_pause,
+            replay_save, replay_start, replay_stop,
+        },
+        rng::rng_seed,
+        save::{continue_available, run_restore, save_path_init, world_restore, RunSnapshot},
+        settings::{
+            bindings_capture, bindings_labels, rebinding_inactive, rebinding_reset,
+            settings_adjust, settings_apply, settings_labels, settings_load, settings_save,
+        },
+        state::{game_reset, pause_reset},
+        time::{tick_game_time, FIXED_TIMESTEP_HZ},
+        tuning::{tuning_load, tuning_reload},
     },
+    tuning::{Tuning, TuningLoader},
 };
 
+pub use headless::{HeadlessRun, RunLimit};
+pub use replay::{Replay, ReplayResult};
+
 /// Main game plugin that sets up all game systems
-pub struct GamePlugin;
+#[derive(Default)]
+pub struct GamePlugin {
+    /// Settings for a run without a window or renderer
+    headless: Option<HeadlessRun>,
+    /// Replay to play back in place of the input devices
+    replay: Option<PathBuf>,
+    /// Seed for new runs, in place of the settings or a random one
+    seed: Option<u64>,
+}
+
+impl GamePlugin {
+    /// Run the game logic without a window or renderer, on `MinimalPlugins` in place of
+    /// `DefaultPlugins`, starting a run straight away and ending it at the run's limit
+    pub fn headless(run: HeadlessRun) -> Self {
+        Self {
+            headless: Some(run),
+            ..default()
+        }
+    }
+
+    /// Play back a replay, starting a run straight away with its input
+    pub fn replay(mut self, path: impl Into<PathBuf>) -> Self {
+        self.replay = Some(path.into());
+        self
+    }
+
+    /// Seed new runs' random numbers, so they play out the same each time
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+}
 
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
-        // Setup the time resource
-        app.init_resource::<resources::GameTime>().add_systems(
+        // Setup the headless plugins, stepping one fixed timestep per update
+        if let Some(run) = &self.headless {
+            app.add_plugins((
+                MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Duration::ZERO)),
+                LogPlugin::default(),
+                StatesPlugin,
+                AssetPlugin::default(),
+                InputPlugin,
+                TransformPlugin,
+                HierarchyPlugin,
+            ))
+            // Store the meshes and materials the game creates, without rendering them
+            .init_asset::<Mesh>()
+            .init_asset::<StandardMaterial>()
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+                1.0 / FIXED_TIMESTEP_HZ,
+            )))
+            .insert_resource(run.clone())
+            .add_systems(Startup, script_load)
+            .add_systems(Last, headless_finish);
+        }
+
+        // Load the settings before any system reads them, keeping headless runs to the
+        // defaults so the user's settings can't change them
+        app.insert_resource(match self.headless {
+            Some(_) => resources::GameSettings::default(),
+            None => settings_load(),
+        });
+
+        // Setup the tuning, hot-reloaded from disk in dev builds
+        app.init_resource::<Tuning>()
+            .init_asset::<Tuning>()
+            .init_asset_loader::<TuningLoader>()
+            .add_systems(Startup, tuning_load)
+            .add_systems(
+                Update,
+                tuning_reload.run_if(resource_exists::<resources::TuningHandle>),
+            );
+
+        // Setup the time resource, ticked by the fixed timestep simulation
+        app.init_resource::<resources::GameTime>()
+            .init_resource::<resources::GameTick>()
+            .insert_resource(Time::<Fixed>::from_hz(FIXED_TIMESTEP_HZ))
+            .add_systems(
+                FixedUpdate,
+                tick_game_time
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(in_state(PauseState::Running)),
+            );
+
+        // Setup the input actions
+        app.init_resource::<ButtonInput<resources::Action>>()
+            .init_resource::<resources::AnalogMovement>()
+            .add_systems(Startup, bindings_load)
+            .add_systems(
+                PreUpdate,
+                update_actions
+                    .after(InputSystem)
+                    .run_if(not(resource_exists::<InputScript>)),
+            )
+            // Hold scripted and replayed input in place of the input devices
+            .add_systems(
+                PreUpdate,
+                actions_clear
+                    .after(InputSystem)
+                    .run_if(resource_exists::<InputScript>),
+            )
+            .add_systems(
+                FixedUpdate,
+                script_actions
+                    .before(tick_game_time)
+                    .run_if(resource_exists::<InputScript>)
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(in_state(PauseState::Running)),
+            )
+            .add_systems(
+                Update,
+                bindings_save.run_if(
+                    resource_changed::<resources::InputBindings>
+                        .and(not(resource_added::<resources::InputBindings>)),
+                ),
+            );
+
+        // Setup the game state and level progression
+        app.init_resource::<resources::GameState>()
+            .init_resource::<resources::LevelThresholds>()
+            .add_event::<events::ScoreEvent>()
+            .add_event::<events::LevelUp>()
+            .add_systems(
+                FixedUpdate,
+                (score_update, level_progression)
+                    .chain()
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(in_state(PauseState::Running)),
+            );
+
+        // Setup the application state, going straight into a run when headless or replaying
+        match (&self.headless, &self.replay) {
+            (None, None) => app.init_state::<AppState>(),
+            _ => app.insert_state(AppState::Loading),
+        }
+        // Despawn entities scoped to a state when leaving it
+        .enable_state_scoped_entities::<AppState>()
+        // Setup the pause state
+        .init_state::<PauseState>()
+        // Setup the settings state
+        .init_state::<SettingsState>()
+        // Setup the menu selection resource
+        .init_resource::<MenuSelection>()
+        // Setup the menu navigation repeat
+        .init_resource::<MenuRepeat>()
+        // Setup the camera, following the player's rendered position with effects on top
+        .add_event::<events::CameraShake>()
+        .add_event::<events::CameraZoom>()
+        .add_event::<events::CameraMove>()
+        .add_systems(Startup, camera_setup)
+        .add_systems(OnEnter(AppState::InGame), camera_effects_reset)
+        .add_systems(
+            Update,
+            (
+                camera_events,
+                (camera_follow, camera_effects)
+                    .chain()
+                    .run_if(in_state(PauseState::Running)),
+            )
+                .chain()
+                .run_if(in_state(AppState::InGame)),
+        )
+        // Setup the start menu
+        .add_systems(OnEnter(AppState::Menu), StartMenu::setup)
+        .add_systems(
+            Update,
+            (
+                StartMenu::navigate,
+                StartMenu::action_input,
+                StartMenu::mouse_input,
+                StartMenu::update_visuals,
+            )
+                .run_if(in_state(AppState::Menu))
+                .run_if(in_state(SettingsState::Closed)),
+        )
+        .add_systems(OnExit(AppState::Menu), StartMenu::cleanup)
+        // Setup the saved run, continued from the start menu
+        .add_systems(Update, continue_available.run_if(in_state(AppState::Menu)))
+        // Setup the levels, loading them all as the run starts
+        .init_asset::<LevelDefinition>()
+        .init_asset_loader::<LevelLoader>()
+        .init_resource::<resources::CurrentLevel>()
+        .add_systems(OnEnter(AppState::Loading), level_load)
+        .add_systems(
+            Update,
+            level_loading
+                .run_if(in_state(AppState::Loading))
+                .run_if(resource_exists::<resources::LevelFolder>),
+        )
+        .add_systems(
+            FixedUpdate,
+            (
+                level_advance,
+                level_reload,
+                (level_spawn, level_spawn_point)
+                    .run_if(resource_changed::<resources::CurrentLevel>),
+                level_view.run_if(
+                    resource_changed::<resources::CurrentLevel>.or(resource_changed::<Tuning>),
+                ),
+                level_intro.run_if(resource_changed::<resources::CurrentLevel>),
+                player_tuning.run_if(resource_changed::<Tuning>),
+                (run_restore, world_restore)
+                    .chain()
+                    .run_if(resource_exists::<RunSnapshot>),
+            )
+                .chain()
+                .before(tick_game_time)
+                .run_if(in_state(AppState::InGame))
+                .run_if(in_state(PauseState::Running)),
+        )
+        // Setup the run lifecycle
+        .add_systems(OnEnter(AppState::InGame), game_reset)
+        .add_systems(OnExit(AppState::InGame), pause_reset)
+        // Setup the game over menu
+        .add_systems(OnEnter(AppState::GameOver), GameOverMenu::setup)
+        .add_systems(
+            Update,
+            (
+                GameOverMenu::navigate,
+                GameOverMenu::action_input,
+                GameOverMenu::mouse_input,
+                GameOverMenu::update_visuals,
+            )
+                .run_if(in_state(AppState::GameOver)),
+        )
+        .add_systems(OnExit(AppState::GameOver), GameOverMenu::cleanup)
+        // Setup the in-game menu
+        .add_systems(OnEnter(PauseState::Paused), IngameMenu::setup)
+        .add_systems(
+            Update,
+            (
+                IngameMenu::navigate,
+                IngameMenu::action_input,
+                IngameMenu::mouse_input,
+                IngameMenu::update_visuals,
+            )
+                .run_if(in_state(AppState::InGame))
+                .run_if(in_state(PauseState::Paused))
+                .run_if(in_state(SettingsState::Closed)),
+        )
+        .add_systems(OnExit(PauseState::Paused), IngameMenu::cleanup)
+        // Setup the in-game pause toggle
+        .add_systems(
+            Update,
+            pause_toggle
+                .run_if(in_state(AppState::InGame))
+                .run_if(in_state(SettingsState::Closed)),
+        )
+        // Setup the player
+        .add_systems(OnEnter(AppState::InGame), player_setup)
+        .add_systems(
+            FixedUpdate,
+            player_movement
+                .after(script_actions)
+                .before(apply_velocity)
+                .run_if(in_state(AppState::InGame))
+                .run_if(in_state(PauseState::Running)),
+        )
+        .add_systems(
             Update,
-            tick_game_time
+            player_animate
                 .run_if(in_state(AppState::InGame))
                 .run_if(in_state(PauseState::Running)),
+        )
+        // Setup the obstacles and damage
+        .add_event::<events::Damage>()
+        .add_systems(
+            FixedUpdate,
+            (hazard_spawn, lifetime_expire, hazard_hit, player_damage)
+                .chain()
+                .after(collision_resolve)
+                .run_if(in_state(AppState::InGame))
+                .run_if(in_state(PauseState::Running)),
+        )
+        // Setup the pickups
+        .add_event::<events::PickupCollected>()
+        .add_systems(
+            FixedUpdate,
+            (pickup_spawn, pickup_collect, pickup_apply, power_up_effects)
+                .chain()
+                .after(collision_resolve)
+                .before(score_update)
+                .run_if(in_state(AppState::InGame))
+                .run_if(in_state(PauseState::Running)),
+        )
+        // Setup the HUD
+        .add_systems(OnEnter(AppState::InGame), hud_setup)
+        .add_systems(
+            Update,
+            (
+                hud_time.run_if(resource_changed::<resources::GameTime>),
+                hud_progress.run_if(resource_changed::<resources::GameState>),
+                hud_lives,
+                hud_visibility.run_if(state_changed::<PauseState>),
+            )
+                .run_if(in_state(AppState::InGame)),
+        )
+        // Setup the movement and collisions, simulated in fixed timesteps
+        .init_resource::<resources::SpatialHash>()
+        .init_resource::<resources::Collisions>()
+        .add_event::<events::CollisionStart>()
+        .add_event::<events::CollisionStay>()
+        .add_systems(
+            FixedUpdate,
+            (
+                hazard_patrol,
+                apply_velocity,
+                collision_detect,
+                collision_resolve,
+                player_bounds,
+            )
+                .chain()
+                .after(tick_game_time)
+                .run_if(in_state(AppState::InGame))
+                .run_if(in_state(PauseState::Running)),
+        )
+        // Setup the rendered positions, interpolated between fixed timesteps
+        .add_systems(
+            RunFixedMainLoop,
+            interpolation_restore.in_set(RunFixedMainLoopSystem::BeforeFixedMainLoop),
+        )
+        .add_systems(FixedFirst, interpolation_store)
+        .add_systems(
+            RunFixedMainLoop,
+            interpolation_apply.in_set(RunFixedMainLoopSystem::AfterFixedMainLoop),
         );
 
-        // Setup the application state
-        app.init_state::<AppState>()
-            // Setup the pause state
-            .init_state::<PauseState>()
-            // Setup the menu selection resource
-            .init_resource::<MenuSelection>()
-            // Setup the camera
-            .add_systems(Startup, camera_setup)
-            // Setup the start menu
-            .add_systems(OnEnter(AppState::Menu), StartMenu::setup)
-            .add_systems(
-                Update,
-                (
-                    StartMenu::keyboard_input,
-                    StartMenu::mouse_input,
-                    StartMenu::update_visuals,
-                )
-                    .run_if(in_state(AppState::Menu)),
-            )
-            .add_systems(OnExit(AppState::Menu), StartMenu::cleanup)
-            // Setup the in-game menu
-            .add_systems(OnEnter(PauseState::Paused), IngameMenu::setup)
+        // Setup the random numbers, seeded as each run starts
+        app.init_resource::<resources::GameRng>()
+            .add_systems(OnEnter(AppState::InGame), rng_seed);
+        if let Some(seed) = self.seed {
+            app.insert_resource(resources::SeedOverride(seed));
+        }
+
+        // Setup the replays, recording each run and playing one back if requested
+        app.add_systems(OnEnter(AppState::InGame), replay_start.after(rng_seed))
             .add_systems(
-                Update,
-                (
-                    IngameMenu::keyboard_input,
-                    IngameMenu::mouse_input,
-                    IngameMenu::update_visuals,
-                )
+                FixedUpdate,
+                replay_record
+                    .after(script_actions)
+                    .before(tick_game_time)
+                    .run_if(resource_exists::<resources::ReplayRecording>)
                     .run_if(in_state(AppState::InGame))
-                    .run_if(in_state(PauseState::Paused)),
+                    .run_if(in_state(PauseState::Running)),
+            )
+            .add_systems(
+                OnEnter(PauseState::Paused),
+                replay_record_pause.run_if(resource_exists::<resources::ReplayRecording>),
+            )
+            .add_systems(
+                OnExit(AppState::InGame),
+                replay_save.run_if(resource_exists::<resources::ReplayRecording>),
+            )
+            // Save the recording when quitting mid-run
+            .add_systems(
+                Last,
+                replay_save
+                    .after(headless_finish)
+                    .run_if(on_event::<AppExit>)
+                    .run_if(resource_exists::<resources::ReplayRecording>),
             )
-            .add_systems(OnExit(PauseState::Paused), IngameMenu::cleanup)
-            // Setup the in-game pause toggle
-            .add_systems(Update, pause_toggle.run_if(in_state(AppState::InGame)))
-            // Setup the player
-            .add_systems(OnEnter(AppState::InGame), player_setup)
             .add_systems(
                 Update,
-                (player_movement, player_animate)
+                replay_pause
+                    .before(pause_toggle)
+                    .run_if(resource_exists::<resources::ReplayPlayback>)
                     .run_if(in_state(AppState::InGame))
-                    .run_if(in_state(PauseState::Running)),
+                    .run_if(in_state(SettingsState::Closed)),
+            );
+        if let Some(path) = &self.replay {
+            app.insert_resource(resources::ReplaySource(path.clone()))
+                .add_systems(Startup, replay_load);
+        }
+        // Hand control back to the player once a replay ends, unless the run ends with it
+        if self.headless.is_none() {
+            app.add_systems(Update, replay_stop.run_if(replay_finished))
+                .add_systems(OnExit(AppState::InGame), replay_stop);
+        }
+
+        // Setup the save file, leaving the user's save alone in headless runs
+        if self.headless.is_none() {
+            app.add_systems(Startup, save_path_init);
+        }
+
+        // Setup the settings
+        app.add_systems(
+            Update,
+            (
+                settings_apply.run_if(resource_changed::<resources::GameSettings>),
+                settings_save.run_if(
+                    resource_changed::<resources::GameSettings>
+                        .and(not(resource_added::<resources::GameSettings>)),
+                ),
+            ),
+        )
+        // Hide the parent menu while the settings are open
+        .add_systems(
+            OnTransition {
+                exited: SettingsState::Closed,
+                entered: SettingsState::Open,
+            },
+            (StartMenu::cleanup, IngameMenu::cleanup),
+        )
+        .add_systems(
+            OnTransition {
+                exited: SettingsState::Open,
+                entered: SettingsState::Closed,
+            },
+            (
+                StartMenu::setup.run_if(in_state(AppState::Menu)),
+                IngameMenu::setup.run_if(in_state(PauseState::Paused)),
+            ),
+        )
+        // Setup the settings menu
+        .add_systems(OnEnter(SettingsState::Open), SettingsMenu::setup)
+        .add_systems(
+            Update,
+            (
+                SettingsMenu::navigate,
+                SettingsMenu::action_input,
+                SettingsMenu::mouse_input,
+                SettingsMenu::update_visuals,
+                settings_adjust,
+                settings_labels,
+            )
+                .run_if(in_state(SettingsState::Open)),
+        )
+        .add_systems(OnExit(SettingsState::Open), SettingsMenu::cleanup)
+        // Setup the key bindings menu
+        .init_resource::<Rebinding>()
+        .add_systems(OnEnter(SettingsState::Bindings), BindingsMenu::setup)
+        .add_systems(
+            Update,
+            (
+                (BindingsMenu::navigate, BindingsMenu::action_input).run_if(rebinding_inactive),
+                bindings_capture,
+                BindingsMenu::mouse_input,
+                BindingsMenu::update_visuals,
+                bindings_labels,
+            )
+                .chain()
+                .run_if(in_state(SettingsState::Bindings)),
+        )
+        .add_systems(
+            OnExit(SettingsState::Bindings),
+            (BindingsMenu::cleanup, rebinding_reset),
+        );
+
+        // Setup the audio, only when there is an audio plugin to play it
+        app.add_event::<events::PlaySfx>();
+        if app.is_plugin_added::<AudioPlugin>() {
+            app.add_systems(Startup, audio_load).add_systems(
+                Update,
+                (
+                    audio_check,
+                    music_change.run_if(state_changed::<AppState>),
+                    music_fade,
+                    audio_volume,
+                    sfx_play,
+                    menu_hover_sound,
+                ),
             );
+        }
     }
 }
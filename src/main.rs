@@ -1,13 +1,170 @@
 #![allow(unused_crate_dependencies)]
 use bevy::prelude::*;
-use dorian::GamePlugin;
-
-fn main() {
-    App::new()
-        // Add Bevy's default plugins (windowing, rendering, input, etc.)
-        .add_plugins(DefaultPlugins)
-        // Add our game plugin
-        .add_plugins(GamePlugin)
-        // Run the app
-        .run();
+use dorian::{GamePlugin, HeadlessRun, RunLimit};
+use std::{env, path::PathBuf};
+
+/// Command line usage
+const USAGE: &str = "Usage: dorian [--seed <seed>] [--replay <file.replay.ron>] \
+    [--headless [--ticks <count> | --seconds <seconds>] [--input <script.ron>] \
+    [--record <file.replay.ron>]]";
+
+/// Length of a headless run when no limit is given, in seconds of game time
+const HEADLESS_SECONDS: f32 = 60.0;
+
+/// How the game was asked to run
+enum Command {
+    Play {
+        replay: Option<PathBuf>,
+        seed: Option<u64>,
+    },
+    Headless {
+        run: HeadlessRun,
+        replay: Option<PathBuf>,
+        seed: Option<u64>,
+    },
+    Help,
+}
+
+fn main() -> AppExit {
+    // Parse the command line
+    let command: Command = match parse_args(env::args().skip(1)) {
+        Ok(command) => command,
+        Err(message) => {
+            eprintln!("{message}\n{USAGE}");
+            return AppExit::from_code(2);
+        }
+    };
+
+    match command {
+        Command::Play { replay, seed } => App::new()
+            // Add Bevy's default plugins (windowing, rendering, input, etc.)
+            .add_plugins(DefaultPlugins)
+            // Add our game plugin, playing back a replay or seeding runs if given
+            .add_plugins(with_options(GamePlugin::default(), replay, seed))
+            // Run the app
+            .run(),
+        // Run the game logic without a window or renderer
+        Command::Headless { run, replay, seed } => App::new()
+            .add_plugins(with_options(GamePlugin::headless(run), replay, seed))
+            .run(),
+        Command::Help => {
+            println!("{USAGE}");
+            AppExit::Success
+        }
+    }
+}
+
+/// Parse the command line arguments
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut headless: bool = false;
+    let mut options: bool = false;
+    let mut limit: Option<RunLimit> = None;
+    let mut run = HeadlessRun::seconds(HEADLESS_SECONDS);
+    let mut replay: Option<PathBuf> = None;
+    let mut seed: Option<u64> = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--headless" => headless = true,
+            "--ticks" => {
+                // Reject a limit that would end the run before it starts
+                let ticks: u64 = parse_value(&arg, args.next())?;
+                if ticks == 0 {
+                    return Err(format!("{arg} must be a positive number, not {ticks}"));
+                }
+                limit = Some(RunLimit::Ticks(ticks));
+            }
+            "--seconds" => {
+                // Reject limits that would never end the run, or end it before it starts
+                let seconds: f32 = parse_value(&arg, args.next())?;
+                if !seconds.is_finite() || seconds <= 0.0 {
+                    return Err(format!("{arg} must be a positive number, not {seconds}"));
+                }
+                limit = Some(RunLimit::Seconds(seconds));
+            }
+            "--input" => run.input = Some(parse_value(&arg, args.next())?),
+            "--record" => run.record = Some(parse_value(&arg, args.next())?),
+            "--replay" => replay = Some(parse_value(&arg, args.next())?),
+            "--seed" => seed = Some(parse_value(&arg, args.next())?),
+            "--help" | "-h" => return Ok(Command::Help),
+            _ => return Err(format!("Unknown argument {arg}")),
+        }
+        options |= matches!(
+            arg.as_str(),
+            "--ticks" | "--seconds" | "--input" | "--record"
+        );
+    }
+
+    // Play a replay out in full unless limited
+    run.limit = match (limit, &replay) {
+        (Some(limit), _) => limit,
+        (None, Some(_)) => RunLimit::Ticks(u64::MAX),
+        (None, None) => run.limit,
+    };
+
+    match (headless, options) {
+        _ if run.input.is_some() && replay.is_some() => {
+            Err("Input scripts and replays can't be used together".to_string())
+        }
+        _ if run.record.is_some() && replay.is_some() => {
+            Err("Replays can't be recorded again with --record".to_string())
+        }
+        _ if seed.is_some() && replay.is_some() => {
+            Err("Replays use their own seed, so --seed can't be used with them".to_string())
+        }
+        (true, _) => Ok(Command::Headless { run, replay, seed }),
+        (false, true) => {
+            Err("Run limits, input scripts and recordings need --headless".to_string())
+        }
+        (false, false) => Ok(Command::Play { replay, seed }),
+    }
+}
+
+/// Play back a replay or seed new runs, if given
+fn with_options(plugin: GamePlugin, replay: Option<PathBuf>, seed: Option<u64>) -> GamePlugin {
+    let plugin: GamePlugin = match replay {
+        Some(path) => plugin.replay(path),
+        None => plugin,
+    };
+
+    match seed {
+        Some(seed) => plugin.seed(seed),
+        None => plugin,
+    }
+}
+
+/// Parse the value following an option
+fn parse_value<T: std::str::FromStr>(option: &str, value: Option<String>) -> Result<T, String> {
+    value
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| format!("Missing or invalid value for {option}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parse a headless run limited by an option
+    fn parse_limit(option: &str, value: &str) -> Result<Command, String> {
+        parse_args(["--headless", option, value].map(String::from).into_iter())
+    }
+
+    #[test]
+    fn limits_must_be_positive() {
+        for seconds in ["NaN", "inf", "-inf", "0", "-1.5"] {
+            assert!(parse_limit("--seconds", seconds).is_err(), "{seconds}");
+        }
+        assert!(matches!(
+            parse_limit("--seconds", "2.5"),
+            Ok(Command::Headless { run, .. }) if run.limit == RunLimit::Seconds(2.5)
+        ));
+
+        for ticks in ["0", "-1", "1.5"] {
+            assert!(parse_limit("--ticks", ticks).is_err(), "{ticks}");
+        }
+        assert!(matches!(
+            parse_limit("--ticks", "64"),
+            Ok(Command::Headless { run, .. }) if run.limit == RunLimit::Ticks(64)
+        ));
+    }
 }
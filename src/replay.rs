@@ -0,0 +1,192 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+// Modules
+use crate::{
+    error::{Error, ErrorLevel},
+    headless::InputScript,
+};
+
+/// Recorded input for a run, with the seed and result needed to reproduce and check it
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Replay {
+    /// Replay format version
+    pub version: u32,
+    /// Seed for the run's random numbers
+    pub seed: u64,
+    /// Input held from each fixed timestep
+    pub input: InputScript,
+    /// Fixed timesteps the run was paused at, once for each pause
+    #[serde(default)]
+    pub pauses: Vec<u64>,
+    /// Fixed timesteps the run lasted
+    pub ticks: u64,
+    /// How the run ended, if it was recorded to the end
+    #[serde(default)]
+    pub result: Option<ReplayResult>,
+}
+
+/// Score and level reached at the end of a recorded run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplayResult {
+    pub score: u32,
+    pub level: u32,
+}
+
+impl Replay {
+    /// Replay format version
+    pub const VERSION: u32 = 1;
+
+    /// Directory for recorded replays, in the platform config directory
+    pub const DIR_NAME: &str = "replays";
+
+    /// Replay file extension
+    pub const EXTENSION: &str = "replay.ron";
+
+    /// Number of recorded replays kept in the replay directory
+    pub const KEEP: usize = 20;
+
+    /// Start recording a run
+    pub fn new(seed: u64) -> Self {
+        Self {
+            version: Self::VERSION,
+            seed,
+            ..default()
+        }
+    }
+
+    /// Load a replay, checking its version
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        // Read the file
+        let contents: String = fs::read_to_string(path).map_err(|err| {
+            Error::new(
+                ErrorLevel::Error,
+                &format!("Unable to read replay {}", path.display()),
+                Some(Box::new(err)),
+            )
+        })?;
+
+        // Parse the replay
+        let replay: Self = ron::from_str(&contents).map_err(|err| {
+            Error::new(
+                ErrorLevel::Error,
+                &format!("Unable to parse replay {}", path.display()),
+                Some(Box::new(err)),
+            )
+        })?;
+
+        match replay.version {
+            Self::VERSION => Ok(replay),
+            version => Err(Error::new(
+                ErrorLevel::Error,
+                &format!(
+                    "Replay {} is version {version}, expected {}",
+                    path.display(),
+                    Self::VERSION
+                ),
+                None,
+            )),
+        }
+    }
+
+    /// Save a replay on a single line, creating its directory if needed
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        // Serialise the replay
+        let contents: String = ron::ser::to_string(self).map_err(|err| {
+            Error::new(
+                ErrorLevel::Warning,
+                &format!("Unable to serialise replay {}", path.display()),
+                Some(Box::new(err)),
+            )
+        })?;
+
+        // Create the replay directory
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|err| {
+                Error::new(
+                    ErrorLevel::Warning,
+                    &format!("Unable to create replay directory {}", dir.display()),
+                    Some(Box::new(err)),
+                )
+            })?;
+        }
+
+        // Write the file
+        fs::write(path, contents).map_err(|err| {
+            Error::new(
+                ErrorLevel::Warning,
+                &format!("Unable to write replay {}", path.display()),
+                Some(Box::new(err)),
+            )
+        })
+    }
+
+    /// Delete the oldest replays in a directory, keeping the newest `KEEP`
+    pub fn prune(dir: &Path) -> Result<(), Error> {
+        // List the replays, oldest first as they're named by when they were saved
+        let entries: fs::ReadDir = fs::read_dir(dir).map_err(|err| {
+            Error::new(
+                ErrorLevel::Warning,
+                &format!("Unable to read replay directory {}", dir.display()),
+                Some(Box::new(err)),
+            )
+        })?;
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.ends_with(Self::EXTENSION))
+            })
+            .collect();
+        paths.sort();
+
+        // Delete all but the newest
+        let excess: usize = paths.len().saturating_sub(Self::KEEP);
+        for path in paths.into_iter().take(excess) {
+            fs::remove_file(&path).map_err(|err| {
+                Error::new(
+                    ErrorLevel::Warning,
+                    &format!("Unable to delete replay {}", path.display()),
+                    Some(Box::new(err)),
+                )
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_newest_replays_are_kept() {
+        let dir: PathBuf =
+            std::env::temp_dir().join(format!("dorian-replays-{}", std::process::id()));
+        assert!(fs::create_dir_all(&dir).is_ok());
+
+        // More replays than are kept, and a file that isn't a replay
+        let names: Vec<String> = (0..Replay::KEEP + 3)
+            .map(|index| format!("2026-01-01_00-00-{index:02}.{}", Replay::EXTENSION))
+            .collect();
+        for name in names.iter().map(String::as_str).chain(["notes.txt"]) {
+            assert!(fs::write(dir.join(name), "").is_ok());
+        }
+
+        let pruned: Result<(), Error> = Replay::prune(&dir);
+        let kept: Vec<bool> = names.iter().map(|name| dir.join(name).exists()).collect();
+        let notes: bool = dir.join("notes.txt").exists();
+        fs::remove_dir_all(&dir).ok();
+
+        assert!(pruned.is_ok());
+        assert_eq!(kept.iter().filter(|kept| !**kept).count(), 3);
+        assert!(kept.iter().skip(3).all(|kept| *kept));
+        assert!(notes);
+    }
+}
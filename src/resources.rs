@@ -1,11 +1,24 @@
-use bevy::prelude::*;
+use bevy::{
+    asset::LoadedFolder,
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use parse_display::Display;
+use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::PathBuf};
+
+// Modules
+use crate::{level::LevelDefinition, replay::Replay, tuning::Tuning};
 
 /// App state
 #[derive(States, Debug, Clone, Eq, PartialEq, Hash, Default)]
 pub enum AppState {
     #[default]
     Menu,
+    Loading,
     InGame,
+    GameOver,
 }
 
 /// Pause state
@@ -16,11 +29,87 @@ pub enum PauseState {
     Paused,
 }
 
+/// Settings menu state
+#[derive(States, Debug, Clone, Eq, PartialEq, Hash, Default)]
+pub enum SettingsState {
+    #[default]
+    Closed,
+    Open,
+    Bindings,
+}
+
 /// Game state
-#[derive(Resource, Default)]
+#[derive(Resource, Debug)]
 pub struct GameState {
-    _score: u32,
-    _level: u32,
+    score: u32,
+    level: u32,
+}
+
+impl Default for GameState {
+    fn default() -> Self {
+        Self { score: 0, level: 1 }
+    }
+}
+
+impl GameState {
+    /// Create a game state, such as one restored from a save
+    pub fn new(score: u32, level: u32) -> Self {
+        Self { score, level }
+    }
+
+    /// Getter for the current score
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    /// Getter for the current level
+    pub fn level(&self) -> u32 {
+        self.level
+    }
+
+    /// Add points to the score
+    pub fn add_score(&mut self, points: u32) {
+        self.score = self.score.saturating_add(points);
+    }
+
+    /// Advance to the next level
+    pub fn level_up(&mut self) -> u32 {
+        self.level += 1;
+        self.level
+    }
+
+    /// Get the difficulty multiplier for the current level, 25% harder per level
+    pub fn difficulty(&self) -> f32 {
+        1.0 + 0.25 * self.level.saturating_sub(1) as f32
+    }
+}
+
+/// Level progression thresholds
+#[derive(Resource, Debug)]
+pub struct LevelThresholds {
+    /// Score required to reach each level after the first
+    pub score: Vec<u32>,
+    /// Game time in seconds required to reach each level after the first
+    pub time: Vec<f32>,
+}
+
+impl Default for LevelThresholds {
+    fn default() -> Self {
+        Self {
+            score: vec![100, 250, 500, 1000, 2000],
+            time: vec![60.0, 120.0, 180.0, 240.0, 300.0],
+        }
+    }
+}
+
+impl LevelThresholds {
+    /// Get the level reached for a score and game time
+    pub fn level_for(&self, score: u32, seconds: f32) -> u32 {
+        let by_score: usize = self.score.iter().take_while(|t| score >= **t).count();
+        let by_time: usize = self.time.iter().take_while(|t| seconds >= **t).count();
+
+        1 + by_score.max(by_time) as u32
+    }
 }
 
 /// Game time
@@ -34,16 +123,583 @@ impl GameTime {
     }
 }
 
+/// Seeded random numbers for the current run, with a stream per subsystem
+///
+/// Each subsystem draws from its own stream, forked from the seed by name, so a change in how
+/// many numbers one subsystem draws, such as a level gaining a spawner, doesn't change the
+/// numbers drawn by the others. Runs with the same seed play out the same, and replays and saves
+/// recorded before such a change stay in step wherever it doesn't reach.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct GameRng {
+    seed: u64,
+    streams: HashMap<String, ChaCha8Rng>,
+}
+
+/// Seed and stream positions of a run's random numbers, for save files
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RngState {
+    seed: u64,
+    /// Word position of each stream, split into its high and low halves as RON can't hold a u128
+    streams: BTreeMap<String, (u64, u64)>,
+}
+
+impl GameRng {
+    /// Stream for spawning hazards
+    pub const SPAWNING: &'static str = "spawning";
+
+    /// Stream for pickups and drops
+    pub const LOOT: &'static str = "loot";
+
+    /// Stream for hazard behaviour
+    pub const AI: &'static str = "ai";
+
+    /// Streams for each subsystem, forked as the run starts so saves always hold them
+    const STREAMS: [&'static str; 3] = [Self::SPAWNING, Self::LOOT, Self::AI];
+
+    /// Start a run's random numbers from a seed
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            streams: Self::STREAMS
+                .into_iter()
+                .map(|name| (name.to_string(), fork(seed, name)))
+                .collect(),
+        }
+    }
+
+    /// Getter for the seed
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Get a subsystem's stream, forking it from the seed on first use
+    pub fn stream(&mut self, name: &str) -> &mut ChaCha8Rng {
+        let seed: u64 = self.seed;
+        self.streams
+            .entry(name.to_string())
+            .or_insert_with(|| fork(seed, name))
+    }
+
+    /// Get the seed and how far each stream has been drawn
+    pub fn state(&self) -> RngState {
+        RngState {
+            seed: self.seed,
+            streams: self
+                .streams
+                .iter()
+                .map(|(name, rng)| {
+                    let position: u128 = rng.get_word_pos();
+                    (name.clone(), ((position >> 64) as u64, position as u64))
+                })
+                .collect(),
+        }
+    }
+
+    /// Continue from a saved seed and stream positions
+    pub fn restore(state: &RngState) -> Self {
+        let mut rng: Self = Self::new(state.seed);
+        for (name, (high, low)) in &state.streams {
+            rng.stream(name)
+                .set_word_pos((u128::from(*high) << 64) | u128::from(*low));
+        }
+        rng
+    }
+}
+
+/// Fork a named stream from a seed, numbered by a stable FNV-1a hash of its name
+fn fork(seed: u64, name: &str) -> ChaCha8Rng {
+    let stream: u64 = name.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+
+    let mut rng: ChaCha8Rng = ChaCha8Rng::seed_from_u64(seed);
+    rng.set_stream(stream);
+    rng
+}
+
+/// Seed for new runs, given on the command line
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct SeedOverride(pub u64);
+
+/// Run being recorded, saved as a replay when it ends
+#[derive(Resource, Debug, Default)]
+pub struct ReplayRecording(pub Replay);
+
+/// Replay being played back, in place of the input devices
+#[derive(Resource, Debug)]
+pub struct ReplayPlayback {
+    pub replay: Replay,
+    /// Index of the next recorded pause
+    pub next_pause: usize,
+}
+
+/// Replay file to play back
+#[derive(Resource, Debug)]
+pub struct ReplaySource(pub PathBuf);
+
+/// Save file for runs in progress, absent in headless runs so they leave the user's save alone
+#[derive(Resource, Debug)]
+pub struct SavePath(pub PathBuf);
+
+/// Fixed timesteps simulated in the current run
+#[derive(Resource, Default)]
+pub struct GameTick(pub u64);
+
+impl GameTick {
+    /// Getter for the current fixed timestep
+    pub fn get(&self) -> u64 {
+        self.0
+    }
+}
+
 /// Game settings
-#[derive(Resource)]
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct GameSettings {
-    _master_volume: f32,
+    master_volume: f32,
+    music_volume: f32,
+    sfx_volume: f32,
+    fullscreen: bool,
+    vsync: bool,
+    resolution: UVec2,
+    seed: Option<u64>,
 }
 
 impl Default for GameSettings {
     fn default() -> Self {
         Self {
-            _master_volume: 1.0,
+            master_volume: 1.0,
+            music_volume: 0.8,
+            sfx_volume: 0.8,
+            fullscreen: false,
+            vsync: true,
+            resolution: Self::RESOLUTIONS[0],
+            seed: None,
         }
     }
 }
+
+impl GameSettings {
+    /// Config file name for the settings
+    pub const FILE_NAME: &'static str = "settings.ron";
+
+    /// Current schema version of the settings file
+    pub const VERSION: u32 = 1;
+
+    /// Supported window resolutions
+    pub const RESOLUTIONS: [UVec2; 4] = [
+        UVec2::new(1280, 720),
+        UVec2::new(1600, 900),
+        UVec2::new(1920, 1080),
+        UVec2::new(2560, 1440),
+    ];
+
+    /// Getter for the master volume, from 0 to 1
+    pub fn master_volume(&self) -> f32 {
+        self.master_volume
+    }
+
+    /// Set the master volume, clamped from 0 to 1
+    pub fn set_master_volume(&mut self, volume: f32) {
+        self.master_volume = volume.clamp(0.0, 1.0);
+    }
+
+    /// Getter for the music volume, from 0 to 1
+    pub fn music_volume(&self) -> f32 {
+        self.music_volume
+    }
+
+    /// Set the music volume, clamped from 0 to 1
+    pub fn set_music_volume(&mut self, volume: f32) {
+        self.music_volume = volume.clamp(0.0, 1.0);
+    }
+
+    /// Getter for the sound effects volume, from 0 to 1
+    pub fn sfx_volume(&self) -> f32 {
+        self.sfx_volume
+    }
+
+    /// Set the sound effects volume, clamped from 0 to 1
+    pub fn set_sfx_volume(&mut self, volume: f32) {
+        self.sfx_volume = volume.clamp(0.0, 1.0);
+    }
+
+    /// Getter for fullscreen
+    pub fn fullscreen(&self) -> bool {
+        self.fullscreen
+    }
+
+    /// Set fullscreen
+    pub fn set_fullscreen(&mut self, fullscreen: bool) {
+        self.fullscreen = fullscreen;
+    }
+
+    /// Getter for vsync
+    pub fn vsync(&self) -> bool {
+        self.vsync
+    }
+
+    /// Set vsync
+    pub fn set_vsync(&mut self, vsync: bool) {
+        self.vsync = vsync;
+    }
+
+    /// Getter for the window resolution
+    pub fn resolution(&self) -> UVec2 {
+        self.resolution
+    }
+
+    /// Getter for the seed for new runs, if set, otherwise they're seeded at random
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// Step to the next or previous supported resolution, wrapping around
+    pub fn cycle_resolution(&mut self, forward: bool) {
+        let count: usize = Self::RESOLUTIONS.len();
+        let index: usize = Self::RESOLUTIONS
+            .iter()
+            .position(|resolution| *resolution == self.resolution)
+            .unwrap_or_default();
+
+        let next: usize = if forward {
+            (index + 1) % count
+        } else {
+            (index + count - 1) % count
+        };
+        self.resolution = Self::RESOLUTIONS[next];
+    }
+}
+
+/// Input actions, resolved from the input bindings
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Display, Serialize, Deserialize,
+)]
+#[display(style = "Title Case")]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Confirm,
+    Back,
+    Pause,
+}
+
+impl Action {
+    /// Action values
+    pub const VALUES: [Self; 7] = [
+        Self::MoveUp,
+        Self::MoveDown,
+        Self::MoveLeft,
+        Self::MoveRight,
+        Self::Confirm,
+        Self::Back,
+        Self::Pause,
+    ];
+}
+
+/// Keys and gamepad buttons bound to each input action
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InputBindings {
+    keys: BTreeMap<Action, Vec<KeyCode>>,
+    buttons: BTreeMap<Action, Vec<GamepadButton>>,
+    stick_deadzone: f32,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        Self {
+            keys: BTreeMap::from([
+                (Action::MoveUp, vec![KeyCode::KeyW, KeyCode::ArrowUp]),
+                (Action::MoveDown, vec![KeyCode::KeyS, KeyCode::ArrowDown]),
+                (Action::MoveLeft, vec![KeyCode::KeyA, KeyCode::ArrowLeft]),
+                (Action::MoveRight, vec![KeyCode::KeyD, KeyCode::ArrowRight]),
+                (Action::Confirm, vec![KeyCode::Enter, KeyCode::NumpadEnter]),
+                (Action::Back, vec![KeyCode::Backspace]),
+                (Action::Pause, vec![KeyCode::Escape]),
+            ]),
+            buttons: BTreeMap::from([
+                (Action::MoveUp, vec![GamepadButton::DPadUp]),
+                (Action::MoveDown, vec![GamepadButton::DPadDown]),
+                (Action::MoveLeft, vec![GamepadButton::DPadLeft]),
+                (Action::MoveRight, vec![GamepadButton::DPadRight]),
+                (Action::Confirm, vec![GamepadButton::South]),
+                (Action::Back, vec![GamepadButton::East]),
+                (Action::Pause, vec![GamepadButton::Start]),
+            ]),
+            stick_deadzone: 0.2,
+        }
+    }
+}
+
+impl InputBindings {
+    /// Config file name for the input bindings
+    pub const FILE_NAME: &'static str = "bindings.ron";
+
+    /// Get the keys bound to an action
+    pub fn keys(&self, action: Action) -> &[KeyCode] {
+        self.keys
+            .get(&action)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Bind a key as the primary key for an action, unbinding it from any other action
+    pub fn rebind(&mut self, action: Action, key: KeyCode) {
+        // Replace the primary key, keeping the alternatives
+        let keys: Vec<KeyCode> = std::iter::once(key)
+            .chain(
+                self.keys(action)
+                    .iter()
+                    .skip(1)
+                    .copied()
+                    .filter(|alternative| *alternative != key),
+            )
+            .collect();
+
+        // Unbind the key from every other action
+        for bound in self.keys.values_mut() {
+            bound.retain(|bound_key| *bound_key != key);
+        }
+
+        self.keys.insert(action, keys);
+    }
+
+    /// Get the gamepad buttons bound to an action
+    pub fn buttons(&self, action: Action) -> &[GamepadButton] {
+        self.buttons
+            .get(&action)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Get the gamepad stick deadzone
+    pub fn stick_deadzone(&self) -> f32 {
+        self.stick_deadzone
+    }
+}
+
+/// Analog movement from the gamepad stick, after the deadzone is applied
+#[derive(Resource, Default, Debug)]
+pub struct AnalogMovement(pub Vec2);
+
+impl AnalogMovement {
+    /// Getter for the current analog movement
+    pub fn get(&self) -> Vec2 {
+        self.0
+    }
+}
+
+/// Broad-phase grid of collider bounds, rebuilt every tick
+#[derive(Resource, Debug)]
+pub struct SpatialHash {
+    cell_size: f32,
+    cells: HashMap<IVec2, Vec<Entity>>,
+}
+
+impl Default for SpatialHash {
+    fn default() -> Self {
+        Self::new(Self::CELL_SIZE)
+    }
+}
+
+impl SpatialHash {
+    /// Default cell size, in world units
+    pub const CELL_SIZE: f32 = 2.0;
+
+    /// Create an empty grid with the given cell size
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::default(),
+        }
+    }
+
+    /// Remove every entity from the grid
+    pub fn clear(&mut self) {
+        self.cells.clear();
+    }
+
+    /// Add an entity to every cell its bounds touch
+    pub fn insert(&mut self, entity: Entity, bounds: Rect) {
+        let min: IVec2 = (bounds.min / self.cell_size).floor().as_ivec2();
+        let max: IVec2 = (bounds.max / self.cell_size).floor().as_ivec2();
+
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                self.cells.entry(IVec2::new(x, y)).or_default().push(entity);
+            }
+        }
+    }
+
+    /// Get each pair of entities sharing a cell, ordered so each pair appears once
+    pub fn pairs(&self) -> HashSet<(Entity, Entity)> {
+        let mut pairs: HashSet<(Entity, Entity)> = HashSet::default();
+
+        for entities in self.cells.values() {
+            for (index, &a) in entities.iter().enumerate() {
+                for &b in &entities[index + 1..] {
+                    if a != b {
+                        pairs.insert((a.min(b), a.max(b)));
+                    }
+                }
+            }
+        }
+
+        pairs
+    }
+}
+
+/// Pairs of entities whose colliders overlapped on the last tick
+#[derive(Resource, Default, Debug)]
+pub struct Collisions(pub HashSet<(Entity, Entity)>);
+
+/// Audio files loaded from the assets, dropping missing ones so they are skipped instead of
+/// failing to play
+#[derive(Resource, Default, Debug)]
+pub struct AudioFiles(pub HashMap<&'static str, Handle<AudioSource>>);
+
+impl AudioFiles {
+    /// Get the handle of an audio file, unless it failed to load
+    pub fn get(&self, path: &str) -> Option<Handle<AudioSource>> {
+        self.0.get(path).cloned()
+    }
+}
+
+/// The level being played, keeping its asset loaded so it can be hot-reloaded
+#[derive(Resource, Debug)]
+pub struct CurrentLevel {
+    pub number: u32,
+    pub definition: LevelDefinition,
+    pub handle: Handle<LevelDefinition>,
+}
+
+impl Default for CurrentLevel {
+    fn default() -> Self {
+        Self {
+            number: 1,
+            definition: LevelDefinition::default(),
+            handle: Handle::default(),
+        }
+    }
+}
+
+/// The level files, all loaded as a run starts so level ups don't wait on them
+#[derive(Resource, Debug)]
+pub struct LevelFolder(pub Handle<LoadedFolder>);
+
+/// The tuning asset, applied to the `Tuning` resource whenever it loads or changes
+#[derive(Resource, Debug)]
+pub struct TuningHandle(pub Handle<Tuning>);
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    // Modules
+    use super::*;
+
+    /// Draw the next few numbers from a stream
+    fn draw(rng: &mut GameRng, name: &str) -> Vec<u32> {
+        (0..4).map(|_| rng.stream(name).gen()).collect()
+    }
+
+    #[test]
+    fn streams_are_independent() {
+        let mut rng = GameRng::new(42);
+        let mut other = GameRng::new(42);
+
+        // Drawing from one stream leaves the others where they were
+        draw(&mut rng, GameRng::SPAWNING);
+        assert_eq!(
+            draw(&mut rng, GameRng::LOOT),
+            draw(&mut other, GameRng::LOOT)
+        );
+        assert_eq!(draw(&mut rng, GameRng::AI), draw(&mut other, GameRng::AI));
+        assert_ne!(
+            draw(&mut rng, GameRng::SPAWNING),
+            draw(&mut other, GameRng::SPAWNING)
+        );
+
+        // Each stream draws different numbers, and so does each seed
+        let mut fresh = GameRng::new(42);
+        assert_ne!(
+            draw(&mut fresh, GameRng::SPAWNING),
+            draw(&mut fresh, GameRng::LOOT)
+        );
+        assert_ne!(
+            draw(&mut GameRng::new(42), GameRng::AI),
+            draw(&mut GameRng::new(43), GameRng::AI)
+        );
+    }
+
+    #[test]
+    fn restored_state_resumes_each_stream() {
+        let mut rng = GameRng::new(7);
+        draw(&mut rng, GameRng::SPAWNING);
+        draw(&mut rng, "effects");
+
+        // Save the state through RON, as in a save file
+        let saved: String = ron::to_string(&rng.state()).unwrap_or_default();
+        let state: Option<RngState> = ron::from_str(&saved).ok();
+        assert_eq!(state.as_ref(), Some(&rng.state()));
+        let mut restored = GameRng::restore(&state.unwrap_or_default());
+
+        assert_eq!(restored.seed(), 7);
+        for name in [GameRng::SPAWNING, GameRng::LOOT, "effects"] {
+            assert_eq!(draw(&mut restored, name), draw(&mut rng, name), "{name}");
+        }
+    }
+
+    #[test]
+    fn score_adds_up_and_saturates() {
+        let mut game_state = GameState::default();
+        game_state.add_score(40);
+        game_state.add_score(60);
+        assert_eq!(game_state.score(), 100);
+
+        game_state.add_score(u32::MAX);
+        assert_eq!(game_state.score(), u32::MAX);
+    }
+
+    #[test]
+    fn level_up_counts_from_one() {
+        let mut game_state = GameState::default();
+        assert_eq!(game_state.level(), 1);
+        assert_eq!(game_state.difficulty(), 1.0);
+
+        assert_eq!(game_state.level_up(), 2);
+        assert_eq!(game_state.level_up(), 3);
+        assert_eq!(game_state.difficulty(), 1.5);
+    }
+
+    #[test]
+    fn score_thresholds_reach_levels() {
+        let thresholds = LevelThresholds::default();
+        assert_eq!(thresholds.level_for(0, 0.0), 1);
+        assert_eq!(thresholds.level_for(99, 0.0), 1);
+        assert_eq!(thresholds.level_for(100, 0.0), 2);
+        assert_eq!(thresholds.level_for(499, 0.0), 3);
+        assert_eq!(thresholds.level_for(u32::MAX, 0.0), 6);
+    }
+
+    #[test]
+    fn time_thresholds_reach_levels() {
+        let thresholds = LevelThresholds::default();
+        assert_eq!(thresholds.level_for(0, 59.9), 1);
+        assert_eq!(thresholds.level_for(0, 60.0), 2);
+        assert_eq!(thresholds.level_for(0, 200.0), 4);
+        assert_eq!(thresholds.level_for(0, f32::MAX), 6);
+    }
+
+    #[test]
+    fn the_further_threshold_wins() {
+        let thresholds = LevelThresholds::default();
+
+        // Score ahead of time, then time ahead of score
+        assert_eq!(thresholds.level_for(500, 60.0), 4);
+        assert_eq!(thresholds.level_for(100, 240.0), 5);
+    }
+}
@@ -0,0 +1,165 @@
+use bevy::{asset::LoadState, audio::Volume, prelude::*, utils::HashMap};
+
+// Modules
+use crate::{
+    components::{
+        audio::{AudioChannel, MusicTrack},
+        menu::MenuSelection,
+    },
+    error::{Error, ErrorLevel},
+    events::{PlaySfx, SoundEffect},
+    resources::{AppState, AudioFiles, GameSettings, PauseState},
+};
+
+// Audio constants
+const CROSSFADE_SECONDS: f32 = 1.5;
+const PAUSE_DUCKING: f32 = 0.3;
+const MENU_MUSIC: &str = "audio/music_menu.ogg";
+const GAME_MUSIC: &str = "audio/music_game.ogg";
+
+/// Start loading the audio files, so missing ones are found without blocking startup
+pub fn audio_load(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let files: HashMap<&'static str, Handle<AudioSource>> = [MENU_MUSIC, GAME_MUSIC]
+        .into_iter()
+        .chain(SoundEffect::VALUES.iter().map(SoundEffect::path))
+        .map(|path| (path, asset_server.load(path)))
+        .collect();
+
+    commands.insert_resource(AudioFiles(files));
+}
+
+/// Drop the audio files that failed to load, warning once about each missing one
+pub fn audio_check(asset_server: Res<AssetServer>, mut files: ResMut<AudioFiles>) {
+    files
+        .0
+        .retain(|path, handle| match asset_server.load_state(handle.id()) {
+            LoadState::Failed(err) => {
+                Error::new(
+                    ErrorLevel::Warning,
+                    &format!("Audio file {path} not found, playing without it"),
+                    Some(Box::new(err)),
+                );
+                false
+            }
+            _ => true,
+        });
+}
+
+/// Crossfade to the music for the new app state
+pub fn music_change(
+    mut commands: Commands,
+    state: Res<State<AppState>>,
+    files: Res<AudioFiles>,
+    mut tracks: Query<&mut MusicTrack>,
+) {
+    // Determine the track for the state, keeping the current one between runs
+    let path: &'static str = match state.get() {
+        AppState::Menu => MENU_MUSIC,
+        AppState::InGame => GAME_MUSIC,
+        AppState::Loading | AppState::GameOver => return,
+    };
+
+    // Keep playing the track if it's already playing
+    if tracks
+        .iter()
+        .any(|track| track.path() == path && !track.fading_out())
+    {
+        return;
+    }
+
+    // Fade out the current tracks
+    for mut track in &mut tracks {
+        track.fade_out();
+    }
+
+    // Leave the music silent if its file is missing
+    let Some(handle) = files.get(path) else {
+        return;
+    };
+
+    // Fade in the new track
+    commands.spawn((
+        AudioPlayer::new(handle),
+        PlaybackSettings::LOOP.with_volume(Volume::new(0.0)),
+        AudioChannel::Music,
+        MusicTrack::new(path),
+    ));
+}
+
+/// Step the music crossfade, despawning tracks once they have faded out
+pub fn music_fade(
+    mut commands: Commands,
+    mut tracks: Query<(Entity, &mut MusicTrack)>,
+    time: Res<Time>,
+) {
+    let amount: f32 = time.delta_secs() / CROSSFADE_SECONDS;
+
+    for (entity, mut track) in &mut tracks {
+        if track.step(amount) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Set the volume of each playing sound from the settings, fades and ducking
+pub fn audio_volume(
+    settings: Res<GameSettings>,
+    pause: Res<State<PauseState>>,
+    sinks: Query<(&AudioChannel, Option<&MusicTrack>, &AudioSink)>,
+) {
+    // Duck the music while paused
+    let ducking: f32 = match pause.get() {
+        PauseState::Running => 1.0,
+        PauseState::Paused => PAUSE_DUCKING,
+    };
+
+    for (channel, track, sink) in &sinks {
+        let channel_volume: f32 = match channel {
+            AudioChannel::Music => settings.music_volume() * ducking,
+            AudioChannel::Sfx => settings.sfx_volume(),
+        };
+        let fade: f32 = track.map(MusicTrack::fade).unwrap_or(1.0);
+
+        sink.set_volume(settings.master_volume() * channel_volume * fade);
+    }
+}
+
+/// Play requested sound effects
+pub fn sfx_play(
+    mut commands: Commands,
+    mut sounds: EventReader<PlaySfx>,
+    files: Res<AudioFiles>,
+    settings: Res<GameSettings>,
+) {
+    let volume: f32 = settings.master_volume() * settings.sfx_volume();
+
+    // Skip sounds whose files are missing
+    for handle in sounds
+        .read()
+        .filter_map(|PlaySfx(sound)| files.get(sound.path()))
+    {
+        commands.spawn((
+            AudioPlayer::new(handle),
+            PlaybackSettings::DESPAWN.with_volume(Volume::new(volume)),
+            AudioChannel::Sfx,
+        ));
+    }
+}
+
+/// Play the hover sound when the menu selection moves, but not as a menu opens
+pub fn menu_hover_sound(
+    selection: Res<MenuSelection>,
+    opened: Query<(), Added<Button>>,
+    mut previous: Local<Option<usize>>,
+    mut sounds: EventWriter<PlaySfx>,
+) {
+    // Forget the last menu's selection when a menu opens
+    if !opened.is_empty() {
+        *previous = None;
+    }
+
+    if previous.is_some_and(|index| index != selection.index()) {
+        let _event_id = sounds.send(PlaySfx(SoundEffect::MenuHover));
+    }
+    *previous = Some(selection.index());
+}
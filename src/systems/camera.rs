@@ -1,40 +1,88 @@
 use bevy::prelude::*;
 
+// Modules
+use crate::{
+    components::{
+        camera::{CameraController, CameraEffects},
+        movement::Velocity,
+        player::Player,
+    },
+    events::{CameraMove, CameraShake, CameraZoom},
+    level::{LevelCamera, LevelExtents, LevelLighting},
+    resources::GameTime,
+    tuning::{LightingTuning, Tuning},
+};
+
 /// Set up the 3D camera and lighting
-pub fn camera_setup(mut commands: Commands) {
+pub fn camera_setup(mut commands: Commands, tuning: Res<Tuning>) {
     // Spawn 3D camera
     spawn_camera(&mut commands);
 
     // Add directional light
-    spawn_directional_light(&mut commands);
+    spawn_directional_light(&mut commands, &tuning.lighting);
 
     // Add ambient light
-    spawn_ambient_light(&mut commands);
+    spawn_ambient_light(&mut commands, &tuning.lighting);
 }
 
 /// Spawn the 3D camera
 fn spawn_camera(commands: &mut Commands) {
     // Create the camera
     let camera = Camera3d::default();
-    let camera_transform = Transform::from_xyz(0.0, 3.0, 7.0).looking_at(Vec3::ZERO, Vec3::Y);
+    let controller = CameraController::new(
+        &LevelCamera::default(),
+        LevelExtents::default().rect(),
+        Vec2::ZERO,
+    );
+    let camera_transform = controller.transform();
 
     // Spawn the camera
-    commands.spawn((camera, camera_transform));
+    commands.spawn((
+        camera,
+        controller,
+        CameraEffects::default(),
+        camera_transform,
+    ));
 }
 
-/// Spawn the directional light
-fn spawn_directional_light(commands: &mut Commands) {
-    const ILLUMINANCE: f32 = 10000.0;
-    const SHADOW_DEPTH_BIAS: f32 = 0.02;
-    const SHADOW_NORMAL_BIAS: f32 = 1.8;
+/// Move the camera after the player's rendered position, as the level's camera mode sets
+pub fn camera_follow(
+    mut cameras: Query<(&mut Transform, &mut CameraController, &Projection), Without<Player>>,
+    players: Query<(&Transform, &Velocity), With<Player>>,
+    time: Res<Time>,
+) {
+    let Ok((player, velocity)) = players.get_single() else {
+        return;
+    };
+
+    for (mut transform, mut controller, projection) in &mut cameras {
+        // Get the size of the view, to keep it within the level
+        let half_view: Vec2 = match projection {
+            Projection::Perspective(perspective) => {
+                controller.half_view(perspective.fov, perspective.aspect_ratio)
+            }
+            _ => Vec2::ZERO,
+        };
+
+        controller.follow(
+            player.translation.truncate(),
+            velocity.get(),
+            half_view,
+            time.delta_secs(),
+        );
+        *transform = controller.transform();
+    }
+}
 
+/// Spawn the directional light, until a level sets its own lighting
+fn spawn_directional_light(commands: &mut Commands, tuning: &LightingTuning) {
     // Create the directional light
     let directional_light = DirectionalLight {
         color: Color::WHITE,
-        illuminance: ILLUMINANCE,
-        shadows_enabled: true,
-        shadow_depth_bias: SHADOW_DEPTH_BIAS,
-        shadow_normal_bias: SHADOW_NORMAL_BIAS,
+        illuminance: LevelLighting::default().illuminance * tuning.intensity,
+        shadows_enabled: tuning.shadows_enabled,
+        shadow_depth_bias: tuning.shadow_depth_bias,
+        shadow_normal_bias: tuning.shadow_normal_bias,
     };
     let directional_light_transform =
         Transform::from_rotation(Quat::from_euler(EulerRot::ZYX, 0.0, 1.0, -0.5));
@@ -43,16 +91,90 @@ fn spawn_directional_light(commands: &mut Commands) {
     commands.spawn((directional_light, directional_light_transform));
 }
 
-/// Spawn the ambient light
-fn spawn_ambient_light(commands: &mut Commands) {
-    const BRIGHTNESS: f32 = 75.0;
-
+/// Spawn the ambient light, until a level sets its own lighting
+fn spawn_ambient_light(commands: &mut Commands, tuning: &LightingTuning) {
     // Create the ambient light
     let ambient_light = AmbientLight {
         color: Color::WHITE,
-        brightness: BRIGHTNESS,
+        brightness: LevelLighting::default().ambient_brightness * tuning.intensity,
     };
 
     // Spawn the ambient light
     commands.insert_resource(ambient_light);
 }
+
+/// Start the camera effects requested by events, from the current game time
+pub fn camera_events(
+    mut cameras: Query<(
+        &mut CameraEffects,
+        &CameraController,
+        &Transform,
+        &Projection,
+    )>,
+    mut shakes: EventReader<CameraShake>,
+    mut zooms: EventReader<CameraZoom>,
+    mut moves: EventReader<CameraMove>,
+    game_time: Res<GameTime>,
+) {
+    let Ok((mut effects, controller, transform, projection)) = cameras.get_single_mut() else {
+        return;
+    };
+    let now: f32 = game_time.get();
+
+    // Shake
+    for shake in shakes.read() {
+        effects.shake(shake.trauma);
+    }
+
+    // Zoom from the current field of view
+    if let Projection::Perspective(perspective) = projection {
+        for zoom in zooms.read() {
+            effects.zoom(perspective.fov, zoom.fov, zoom.duration, zoom.ease, now);
+        }
+    }
+
+    // Move from the current shot
+    for CameraMove(path) in moves.read() {
+        let look_at: Vec3 =
+            transform.translation + transform.forward() * controller.offset.length();
+        effects.play(path.clone(), transform.translation, look_at, now);
+    }
+}
+
+/// Layer the camera effects over the camera controller, following the game time between
+/// fixed timesteps
+pub fn camera_effects(
+    mut cameras: Query<(
+        &mut Transform,
+        &mut Projection,
+        &mut CameraEffects,
+        &CameraController,
+    )>,
+    game_time: Res<GameTime>,
+    fixed_time: Res<Time<Fixed>>,
+) {
+    // Sample part way to the next timestep, so effects move smoothly at any frame rate
+    let now: f32 = game_time.get() + fixed_time.overstep().as_secs_f32();
+
+    for (mut transform, mut projection, mut effects, controller) in &mut cameras {
+        // Zoom
+        if let (Some(fov), Projection::Perspective(perspective)) =
+            (effects.fov(now), projection.as_mut())
+        {
+            perspective.fov = fov;
+        }
+
+        // Scripted moves and shake
+        effects.apply(&mut transform, controller, now);
+    }
+}
+
+/// Clear the camera effects and field of view left from the last run
+pub fn camera_effects_reset(mut cameras: Query<(&mut Projection, &mut CameraEffects)>) {
+    for (mut projection, mut effects) in &mut cameras {
+        *effects = CameraEffects::default();
+        if let Projection::Perspective(perspective) = projection.as_mut() {
+            perspective.fov = PerspectiveProjection::default().fov;
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use bevy::{prelude::*, utils::HashSet};
+
+// Modules
+use crate::{
+    components::{
+        collision::{Collider, Solid},
+        movement::Velocity,
+    },
+    events::{CollisionStart, CollisionStay},
+    resources::{Collisions, SpatialHash},
+};
+
+/// Find overlapping colliders and send collision start and stay events
+pub fn collision_detect(
+    colliders: Query<(Entity, &Transform, &Collider)>,
+    mut grid: ResMut<SpatialHash>,
+    mut collisions: ResMut<Collisions>,
+    mut started: EventWriter<CollisionStart>,
+    mut stayed: EventWriter<CollisionStay>,
+) {
+    // Broad phase, bucket the collider bounds into the grid
+    grid.clear();
+    for (entity, transform, collider) in &colliders {
+        grid.insert(entity, collider.bounds(transform.translation.truncate()));
+    }
+
+    // Narrow phase, test the shapes of entities sharing a cell
+    let current: HashSet<(Entity, Entity)> = grid
+        .pairs()
+        .into_iter()
+        .filter(|&(a, b)| {
+            let Ok([(_, a_transform, a_collider), (_, b_transform, b_collider)]) =
+                colliders.get_many([a, b])
+            else {
+                return false;
+            };
+            a_collider
+                .contact(
+                    a_transform.translation.truncate(),
+                    b_collider,
+                    b_transform.translation.truncate(),
+                )
+                .is_some()
+        })
+        .collect();
+
+    // Send the events in a stable order
+    let mut pairs: Vec<(Entity, Entity)> = current.iter().copied().collect();
+    pairs.sort();
+    for (a, b) in pairs {
+        match collisions.0.contains(&(a, b)) {
+            false => {
+                let _event_id = started.send(CollisionStart { a, b });
+            }
+            true => {
+                let _event_id = stayed.send(CollisionStay { a, b });
+            }
+        }
+    }
+
+    collisions.0 = current;
+}
+
+/// Push moving entities out of the solid entities they overlap
+pub fn collision_resolve(
+    collisions: Res<Collisions>,
+    mut bodies: Query<(&mut Transform, &mut Velocity, &Collider), Without<Solid>>,
+    solids: Query<(&Transform, &Collider), With<Solid>>,
+) {
+    // Resolve in a stable order
+    let mut pairs: Vec<(Entity, Entity)> = collisions.0.iter().copied().collect();
+    pairs.sort();
+
+    for (a, b) in pairs {
+        // Find the moving body and the solid it hit
+        let (body, solid) = match (bodies.contains(a), bodies.contains(b)) {
+            (true, false) => (a, b),
+            (false, true) => (b, a),
+            _ => continue,
+        };
+        let (Ok((mut transform, mut velocity, collider)), Ok((solid_transform, solid_collider))) =
+            (bodies.get_mut(body), solids.get(solid))
+        else {
+            continue;
+        };
+
+        // Recheck the overlap, as earlier pushes may have already separated them
+        let Some(push) = collider.contact(
+            transform.translation.truncate(),
+            solid_collider,
+            solid_transform.translation.truncate(),
+        ) else {
+            continue;
+        };
+        transform.translation += push.extend(0.0);
+
+        // Cancel the velocity into the solid
+        let normal: Vec2 = push.normalize_or_zero();
+        let current: Vec2 = velocity.get();
+        let into: f32 = current.dot(normal);
+        if into < 0.0 {
+            velocity.set(current - normal * into);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build an app that detects and resolves collisions every update
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<SpatialHash>()
+            .init_resource::<Collisions>()
+            .add_event::<CollisionStart>()
+            .add_event::<CollisionStay>()
+            .add_systems(Update, (collision_detect, collision_resolve).chain());
+        app
+    }
+
+    /// Spawn a collider at a position
+    fn spawn(app: &mut App, collider: Collider, position: Vec2) -> Entity {
+        app.world_mut()
+            .spawn((Transform::from_translation(position.extend(0.0)), collider))
+            .id()
+    }
+
+    /// Check whether two entities are colliding, in either order
+    fn colliding(app: &App, a: Entity, b: Entity) -> bool {
+        app.world()
+            .resource::<Collisions>()
+            .0
+            .contains(&(a.min(b), a.max(b)))
+    }
+
+    /// Count the events of a type sent since they were last counted
+    fn sent<E: Event>(app: &mut App) -> usize {
+        app.world_mut().resource_mut::<Events<E>>().drain().count()
+    }
+
+    #[test]
+    fn shapes_overlap() {
+        let mut app = app();
+        let cases = [
+            // Boxes overlapping, touching and apart
+            (
+                Collider::aabb(Vec2::ONE),
+                Collider::aabb(Vec2::ONE),
+                0.9,
+                true,
+            ),
+            (
+                Collider::aabb(Vec2::ONE),
+                Collider::aabb(Vec2::ONE),
+                1.0,
+                false,
+            ),
+            // Spheres overlapping and apart
+            (Collider::sphere(0.5), Collider::sphere(0.5), 0.9, true),
+            (Collider::sphere(0.5), Collider::sphere(0.5), 1.1, false),
+            // A sphere against a box's side
+            (Collider::sphere(0.5), Collider::aabb(Vec2::ONE), 0.9, true),
+            (Collider::aabb(Vec2::ONE), Collider::sphere(0.5), 1.1, false),
+        ];
+
+        for (a_collider, b_collider, distance, overlapping) in cases {
+            let a: Entity = spawn(&mut app, a_collider, Vec2::new(0.0, 10.0));
+            let b: Entity = spawn(&mut app, b_collider, Vec2::new(distance, 10.0));
+            app.update();
+
+            assert_eq!(
+                colliding(&app, a, b),
+                overlapping,
+                "{a_collider:?} and {b_collider:?} {distance} apart"
+            );
+            app.world_mut().despawn(a);
+            app.world_mut().despawn(b);
+        }
+
+        // A sphere near a box's corner, inside its bounds but outside its shape
+        let a: Entity = spawn(&mut app, Collider::aabb(Vec2::splat(2.0)), Vec2::ZERO);
+        let b: Entity = spawn(&mut app, Collider::sphere(0.5), Vec2::splat(1.4));
+        app.update();
+        assert!(!colliding(&app, a, b));
+    }
+
+    #[test]
+    fn events_start_then_stay() {
+        let mut app = app();
+        spawn(&mut app, Collider::sphere(0.5), Vec2::ZERO);
+        let moving: Entity = spawn(&mut app, Collider::sphere(0.5), Vec2::new(0.5, 0.0));
+
+        // Start on the first overlapping update, then stay
+        app.update();
+        assert_eq!(
+            (
+                sent::<CollisionStart>(&mut app),
+                sent::<CollisionStay>(&mut app)
+            ),
+            (1, 0)
+        );
+        app.update();
+        assert_eq!(
+            (
+                sent::<CollisionStart>(&mut app),
+                sent::<CollisionStay>(&mut app)
+            ),
+            (0, 1)
+        );
+
+        // Send nothing once they separate
+        app.world_mut()
+            .entity_mut(moving)
+            .insert(Transform::from_xyz(5.0, 0.0, 0.0));
+        app.update();
+        assert_eq!(
+            sent::<CollisionStart>(&mut app) + sent::<CollisionStay>(&mut app),
+            0
+        );
+    }
+
+    #[test]
+    fn solids_push_bodies_out() {
+        let mut app = app();
+        app.world_mut()
+            .spawn((Transform::default(), Collider::aabb(Vec2::ONE), Solid));
+        let body: Entity = app
+            .world_mut()
+            .spawn((
+                Transform::from_xyz(0.8, 0.0, 0.0),
+                Collider::aabb(Vec2::ONE),
+                Velocity { x: -2.0, y: 1.0 },
+            ))
+            .id();
+        app.update();
+
+        // Pushed out of the right side, keeping only the velocity along it
+        let body = app.world().entity(body);
+        let translation: Vec3 = body.get::<Transform>().map_or(Vec3::NAN, |t| t.translation);
+        assert!(
+            translation.abs_diff_eq(Vec3::new(1.0, 0.0, 0.0), 1e-5),
+            "{translation}"
+        );
+        assert_eq!(body.get::<Velocity>(), Some(&Velocity { x: 0.0, y: 1.0 }));
+    }
+
+    #[test]
+    fn spatial_hash_cells_include_their_edges() {
+        let cell_size: f32 = SpatialHash::CELL_SIZE;
+        let mut grid = SpatialHash::new(cell_size);
+        let [a, b, c, d] = [0, 1, 2, 3].map(Entity::from_raw);
+
+        // Bounds ending on a cell edge reach into the next cell
+        grid.insert(a, Rect::new(0.0, 0.0, cell_size, 1.0));
+        grid.insert(b, Rect::new(cell_size, 0.0, cell_size + 1.0, 1.0));
+        // Bounds ending just short of it don't
+        grid.insert(c, Rect::new(-1.0, 0.0, cell_size - 0.01, 1.0));
+        // Bounds across zero fill the cells either side
+        grid.insert(d, Rect::new(-0.5, -0.5, 0.5, 0.5));
+
+        let pairs: HashSet<(Entity, Entity)> = grid.pairs();
+        assert!(pairs.contains(&(a, b)));
+        assert!(!pairs.contains(&(b, c)));
+        assert!(pairs.contains(&(a, c)) && pairs.contains(&(a, d)) && pairs.contains(&(c, d)));
+        assert_eq!(pairs.len(), 4);
+    }
+}
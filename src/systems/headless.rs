@@ -0,0 +1,96 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+// Modules
+use crate::{
+    components::player::{Lives, Player},
+    headless::{HeadlessRun, InputScript},
+    replay::ReplayResult,
+    resources::{AppState, GameRng, GameState, GameTick, GameTime, ReplayPlayback},
+};
+
+/// Load the input script for a headless run, stopping the run if it can't be read
+pub fn script_load(mut commands: Commands, run: Res<HeadlessRun>) {
+    let Some(path) = &run.input else {
+        return;
+    };
+
+    match InputScript::load(path) {
+        Ok(script) => commands.insert_resource(script),
+        // Errors logged automatically within InputScript::load
+        Err(_) => {
+            commands.send_event(AppExit::error());
+        }
+    }
+}
+
+/// Run progress, for the summary of a headless run
+#[derive(SystemParam)]
+pub struct RunSummary<'w, 's> {
+    game_state: Res<'w, GameState>,
+    game_time: Res<'w, GameTime>,
+    game_tick: Res<'w, GameTick>,
+    rng: Res<'w, GameRng>,
+    players: Query<'w, 's, &'static Lives, With<Player>>,
+}
+
+impl RunSummary<'_, '_> {
+    /// Print the summary
+    fn print(&self, outcome: &str) {
+        println!("Headless run finished: {outcome}");
+        println!("  Seed: {}", self.rng.seed());
+        println!("  Ticks: {}", self.game_tick.get());
+        println!("  Game time: {:.2}s", self.game_time.get());
+        println!("  Level: {}", self.game_state.level());
+        println!("  Score: {}", self.game_state.score());
+        println!(
+            "  Lives: {}",
+            self.players.get_single().map_or(0, Lives::get)
+        );
+    }
+}
+
+/// End a headless run at its limit, at the end of its replay or on game over, then print a
+/// summary of the run, failing if it didn't reach the replay's result
+pub fn headless_finish(
+    mut commands: Commands,
+    run: Res<HeadlessRun>,
+    app_state: Res<State<AppState>>,
+    playback: Option<Res<ReplayPlayback>>,
+    summary: RunSummary,
+) {
+    let tick: u64 = summary.game_tick.get();
+    let outcome: &str = match app_state.get() {
+        AppState::GameOver => "Game over",
+        _ if tick >= run.limit.ticks() => "Limit reached",
+        _ if playback
+            .as_ref()
+            .is_some_and(|playback| tick >= playback.replay.ticks) =>
+        {
+            "Replay finished"
+        }
+        _ => return,
+    };
+    summary.print(outcome);
+
+    // Check the run against the replay
+    let expected: Option<ReplayResult> = playback.and_then(|playback| playback.replay.result);
+    let exit: AppExit = match expected {
+        Some(expected)
+            if expected.score != summary.game_state.score()
+                || expected.level != summary.game_state.level() =>
+        {
+            println!(
+                "  Replay diverged: expected level {} and score {}",
+                expected.level, expected.score
+            );
+            AppExit::error()
+        }
+        Some(_) => {
+            println!("  Replay matched");
+            AppExit::Success
+        }
+        None => AppExit::Success,
+    };
+
+    commands.send_event(exit);
+}
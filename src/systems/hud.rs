@@ -0,0 +1,113 @@
+use bevy::prelude::*;
+
+// Modules
+use crate::{
+    components::{
+        hud::{Hud, HudText},
+        player::{Lives, Player},
+    },
+    resources::{AppState, GameState, GameTime, PauseState},
+};
+
+/// Setup and spawn the HUD overlay
+pub fn hud_setup(mut commands: Commands, game_state: Res<GameState>, game_time: Res<GameTime>) {
+    // HUD attributes
+    const HUD_WIDTH: Val = Val::Percent(100.0);
+    const HUD_PADDING: Val = Val::Px(16.0);
+    const HUD_FONT_SIZE: f32 = 24.0;
+
+    commands
+        .spawn((
+            Hud,
+            Node {
+                width: HUD_WIDTH,
+                padding: UiRect::all(HUD_PADDING),
+                justify_content: JustifyContent::SpaceBetween,
+                flex_direction: FlexDirection::Row,
+                ..default()
+            },
+            StateScoped(AppState::InGame),
+        ))
+        .with_children(|parent| {
+            for readout in HudText::VALUES {
+                // Lives are filled in once the player has spawned
+                let label: String = match readout {
+                    HudText::Time => readout.label(format_time(game_time.get())),
+                    HudText::Score => readout.label(game_state.score()),
+                    HudText::Level => readout.label(game_state.level()),
+                    HudText::Lives => readout.label("-"),
+                };
+
+                parent.spawn((
+                    readout,
+                    Text::new(label),
+                    TextFont {
+                        font_size: HUD_FONT_SIZE,
+                        ..default()
+                    },
+                ));
+            }
+        });
+}
+
+/// Show the game time, which only changes the text once a second
+pub fn hud_time(game_time: Res<GameTime>, mut texts: Query<(&HudText, &mut Text)>) {
+    set_readout(
+        &mut texts,
+        HudText::Time,
+        HudText::Time.label(format_time(game_time.get())),
+    );
+}
+
+/// Show the score and level
+pub fn hud_progress(game_state: Res<GameState>, mut texts: Query<(&HudText, &mut Text)>) {
+    set_readout(
+        &mut texts,
+        HudText::Score,
+        HudText::Score.label(game_state.score()),
+    );
+    set_readout(
+        &mut texts,
+        HudText::Level,
+        HudText::Level.label(game_state.level()),
+    );
+}
+
+/// Show the player's lives when they change
+pub fn hud_lives(
+    lives: Query<&Lives, (With<Player>, Changed<Lives>)>,
+    mut texts: Query<(&HudText, &mut Text)>,
+) {
+    if let Ok(lives) = lives.get_single() {
+        set_readout(
+            &mut texts,
+            HudText::Lives,
+            HudText::Lives.label(lives.get()),
+        );
+    }
+}
+
+/// Hide the HUD while paused
+pub fn hud_visibility(pause: Res<State<PauseState>>, mut hud: Query<&mut Visibility, With<Hud>>) {
+    for mut visibility in &mut hud {
+        *visibility = match pause.get() {
+            PauseState::Running => Visibility::Inherited,
+            PauseState::Paused => Visibility::Hidden,
+        };
+    }
+}
+
+/// Set the text of a readout, only touching it when the text changes
+fn set_readout(texts: &mut Query<(&HudText, &mut Text)>, readout: HudText, label: String) {
+    for (_, mut text) in texts.iter_mut().filter(|(text, _)| **text == readout) {
+        if text.0 != label {
+            text.0.clone_from(&label);
+        }
+    }
+}
+
+/// Format seconds as mm:ss
+fn format_time(seconds: f32) -> String {
+    let seconds: u32 = seconds.max(0.0) as u32;
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
+}
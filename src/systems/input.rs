@@ -0,0 +1,266 @@
+use bevy::prelude::*;
+
+// Modules
+use crate::{
+    config::{config_path, load_ron, save_ron},
+    headless::{HeadlessRun, InputScript, ScriptStep},
+    resources::{Action, AnalogMovement, GameTick, InputBindings},
+};
+
+/// Load the input bindings from the config file
+pub fn bindings_load(mut commands: Commands, headless: Option<Res<HeadlessRun>>) {
+    // Use the defaults for headless runs, leaving the user's config dir untouched
+    if headless.is_some() {
+        commands.insert_resource(InputBindings::default());
+        return;
+    }
+
+    // Load the bindings, falling back to the defaults
+    let bindings: InputBindings = match config_path(InputBindings::FILE_NAME)
+        .and_then(|path| load_ron(&path).map(|bindings| (path, bindings)))
+    {
+        // Use the saved bindings
+        Ok((_path, Some(bindings))) => bindings,
+        // Write the defaults so they can be edited
+        Ok((path, None)) => {
+            let bindings = InputBindings::default();
+            save_ron(&path, &bindings).ok();
+            bindings
+        }
+        // Errors logged automatically within the config helpers
+        Err(_err) => InputBindings::default(),
+    };
+
+    commands.insert_resource(bindings);
+}
+
+/// Save the input bindings to the config file
+pub fn bindings_save(bindings: Res<InputBindings>) {
+    config_path(InputBindings::FILE_NAME)
+        .and_then(|path| save_ron(&path, &*bindings))
+        // Errors logged automatically within the config helpers
+        .ok();
+}
+
+/// Resolve the bound keys, gamepad buttons and sticks into input actions
+pub fn update_actions(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    bindings: Res<InputBindings>,
+    mut actions: ResMut<ButtonInput<Action>>,
+    mut analog: ResMut<AnalogMovement>,
+) {
+    // How far the stick must be pushed to count as a direction action
+    const STICK_ACTION_THRESHOLD: f32 = 0.5;
+
+    // Get the stick position from the first gamepad pushed past the deadzone
+    let stick: Vec2 = gamepads
+        .iter()
+        .map(|gamepad| apply_deadzone(gamepad.left_stick(), bindings.stick_deadzone()))
+        .find(|stick| *stick != Vec2::ZERO)
+        .unwrap_or(Vec2::ZERO);
+    analog.0 = stick;
+
+    // Clear the previous frame's just pressed and released actions
+    actions.clear();
+
+    // Press each action while any of its keys or buttons are held
+    for action in Action::VALUES {
+        let key_pressed: bool = keyboard_input.any_pressed(bindings.keys(action).iter().copied());
+        let button_pressed: bool = gamepads
+            .iter()
+            .any(|gamepad| gamepad.any_pressed(bindings.buttons(action).iter().copied()));
+        let stick_pressed: bool = match action {
+            Action::MoveUp => stick.y > STICK_ACTION_THRESHOLD,
+            Action::MoveDown => stick.y < -STICK_ACTION_THRESHOLD,
+            Action::MoveLeft => stick.x < -STICK_ACTION_THRESHOLD,
+            Action::MoveRight => stick.x > STICK_ACTION_THRESHOLD,
+            _ => false,
+        };
+
+        if key_pressed || button_pressed || stick_pressed {
+            actions.press(action);
+        } else {
+            actions.release(action);
+        }
+    }
+}
+
+/// Clear the previous frame's just pressed and released actions, while scripted input holds them
+pub fn actions_clear(mut actions: ResMut<ButtonInput<Action>>) {
+    actions.clear();
+}
+
+/// Hold the scripted actions and stick for each fixed timestep, in place of the input devices
+pub fn script_actions(
+    script: Res<InputScript>,
+    game_tick: Res<GameTick>,
+    mut actions: ResMut<ButtonInput<Action>>,
+    mut analog: ResMut<AnalogMovement>,
+) {
+    let step: Option<&ScriptStep> = script.step(game_tick.get());
+    let held: &[Action] = step.map(|step| step.held.as_slice()).unwrap_or_default();
+    analog.0 = step.map_or(Vec2::ZERO, |step| step.stick);
+
+    // Press each held action and release the rest, leaving pauses to the replay
+    for action in Action::VALUES {
+        match (action, held.contains(&action)) {
+            (Action::Pause, _) => {}
+            (_, true) => actions.press(action),
+            (_, false) => actions.release(action),
+        }
+    }
+}
+
+/// Apply a radial deadzone to a stick, rescaling the rest of its range to start from zero
+fn apply_deadzone(stick: Vec2, deadzone: f32) -> Vec2 {
+    let length: f32 = stick.length();
+    if length <= deadzone || deadzone >= 1.0 {
+        return Vec2::ZERO;
+    }
+
+    // Rescale the live range to 0..1
+    let scaled: f32 = ((length - deadzone) / (1.0 - deadzone)).min(1.0);
+    stick / length * scaled
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::input::{
+        gamepad::{
+            GamepadConnection, GamepadConnectionEvent, RawGamepadAxisChangedEvent,
+            RawGamepadButtonChangedEvent, RawGamepadEvent,
+        },
+        InputPlugin,
+    };
+
+    // Modules
+    use super::*;
+
+    /// Build an app that resolves the input devices into actions, with one gamepad connected
+    fn app() -> (App, Entity) {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, InputPlugin))
+            .init_resource::<InputBindings>()
+            .init_resource::<ButtonInput<Action>>()
+            .init_resource::<AnalogMovement>()
+            .add_systems(Update, update_actions);
+
+        // Connect a gamepad, as the gamepad backend would
+        let gamepad: Entity = app.world_mut().spawn_empty().id();
+        let _event_id = app.world_mut().send_event(GamepadConnectionEvent::new(
+            gamepad,
+            GamepadConnection::Connected {
+                name: String::from("Test gamepad"),
+                vendor_id: None,
+                product_id: None,
+            },
+        ));
+        app.update();
+
+        (app, gamepad)
+    }
+
+    /// Move the left stick for one update
+    fn stick(app: &mut App, gamepad: Entity, position: Vec2) {
+        for (axis, value) in [
+            (GamepadAxis::LeftStickX, position.x),
+            (GamepadAxis::LeftStickY, position.y),
+        ] {
+            let _event_id =
+                app.world_mut()
+                    .send_event(RawGamepadEvent::Axis(RawGamepadAxisChangedEvent::new(
+                        gamepad, axis, value,
+                    )));
+        }
+        app.update();
+    }
+
+    /// Press or release a gamepad button for one update
+    fn button(app: &mut App, gamepad: Entity, button: GamepadButton, pressed: bool) {
+        let value: f32 = match pressed {
+            true => 1.0,
+            false => 0.0,
+        };
+        let _event_id =
+            app.world_mut()
+                .send_event(RawGamepadEvent::Button(RawGamepadButtonChangedEvent::new(
+                    gamepad, button, value,
+                )));
+        app.update();
+    }
+
+    #[test]
+    fn stick_deadzone_is_applied() {
+        let (mut app, gamepad) = app();
+        let deadzone: f32 = InputBindings::default().stick_deadzone();
+
+        // Small pushes are ignored
+        stick(&mut app, gamepad, Vec2::new(deadzone * 0.5, 0.0));
+        assert_eq!(app.world().resource::<AnalogMovement>().get(), Vec2::ZERO);
+        assert!(!app
+            .world()
+            .resource::<ButtonInput<Action>>()
+            .pressed(Action::MoveRight));
+
+        // The rest of the range is rescaled to start from zero
+        stick(&mut app, gamepad, Vec2::new(0.0, (1.0 + deadzone) / 2.0));
+        let analog: Vec2 = app.world().resource::<AnalogMovement>().get();
+        assert!(analog.abs_diff_eq(Vec2::new(0.0, 0.5), 1e-5), "{analog}");
+
+        // A full push reaches full speed and presses the direction action
+        stick(&mut app, gamepad, Vec2::new(-1.0, 0.0));
+        assert_eq!(
+            app.world().resource::<AnalogMovement>().get(),
+            Vec2::new(-1.0, 0.0)
+        );
+        let actions = app.world().resource::<ButtonInput<Action>>();
+        assert!(actions.pressed(Action::MoveLeft));
+        assert!(!actions.pressed(Action::MoveUp));
+    }
+
+    #[test]
+    fn dpad_presses_the_direction_actions() {
+        let (mut app, gamepad) = app();
+
+        button(&mut app, gamepad, GamepadButton::DPadUp, true);
+        let actions = app.world().resource::<ButtonInput<Action>>();
+        assert!(actions.just_pressed(Action::MoveUp));
+        assert!(!actions.pressed(Action::MoveDown));
+
+        // Held across updates without being pressed again
+        app.update();
+        let actions = app.world().resource::<ButtonInput<Action>>();
+        assert!(actions.pressed(Action::MoveUp));
+        assert!(!actions.just_pressed(Action::MoveUp));
+
+        button(&mut app, gamepad, GamepadButton::DPadUp, false);
+        let actions = app.world().resource::<ButtonInput<Action>>();
+        assert!(actions.just_released(Action::MoveUp));
+
+        // The D-pad leaves the analog movement to the stick
+        assert_eq!(app.world().resource::<AnalogMovement>().get(), Vec2::ZERO);
+    }
+
+    #[test]
+    fn buttons_map_to_their_actions() {
+        let (mut app, gamepad) = app();
+
+        for (pressed, action) in [
+            (GamepadButton::South, Action::Confirm),
+            (GamepadButton::East, Action::Back),
+            (GamepadButton::Start, Action::Pause),
+            (GamepadButton::DPadLeft, Action::MoveLeft),
+        ] {
+            button(&mut app, gamepad, pressed, true);
+            let actions = app.world().resource::<ButtonInput<Action>>();
+            assert_eq!(
+                actions.get_pressed().copied().collect::<Vec<Action>>(),
+                vec![action],
+                "{pressed:?}"
+            );
+
+            button(&mut app, gamepad, pressed, false);
+        }
+    }
+}
@@ -0,0 +1,240 @@
+use bevy::{
+    asset::{LoadState, LoadedFolder, UntypedAssetId},
+    math::curve::EaseFunction,
+    prelude::*,
+};
+
+// Modules
+use crate::{
+    components::{
+        camera::CameraController,
+        level::LevelEntity,
+        movement::{Interpolated, Velocity},
+        player::{Player, SpawnPoint},
+    },
+    error::{Error, ErrorLevel},
+    events::{CameraMove, CameraZoom, LevelUp},
+    level::{LevelCamera, LevelDefinition},
+    resources::{AppState, CurrentLevel, GameTime, LevelFolder, TuningHandle},
+    systems::{obstacle::spawn_obstacles, pickup::spawn_pickups, save::RunSnapshot},
+    tuning::Tuning,
+};
+
+// Level constants
+const LEVEL_ZOOM_SECONDS: f32 = 1.0;
+
+/// Load every level file as a run starts, so level ups don't wait on them
+pub fn level_load(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(LevelFolder(asset_server.load_folder(LevelDefinition::DIR)));
+}
+
+/// Start the run once the level and tuning files have loaded, on level 1 or the built-in level
+pub fn level_loading(
+    mut commands: Commands,
+    folder: Res<LevelFolder>,
+    tuning: Option<Res<TuningHandle>>,
+    snapshot: Option<Res<RunSnapshot>>,
+    asset_server: Res<AssetServer>,
+    folders: Res<Assets<LoadedFolder>>,
+    levels: Res<Assets<LevelDefinition>>,
+) {
+    // Wait for each file to load or fail, so none arrive part way through the run
+    let settled = |id: UntypedAssetId| {
+        !matches!(
+            asset_server.load_state(id),
+            LoadState::NotLoaded | LoadState::Loading
+        )
+    };
+    let files: Vec<UntypedAssetId> = folders
+        .get(&folder.0)
+        .map(|folder| folder.handles.iter().map(UntypedHandle::id).collect())
+        .unwrap_or_default();
+    if !settled(folder.0.id().untyped())
+        || !files.into_iter().all(settled)
+        || !tuning.is_none_or(|tuning| settled(tuning.0.id().untyped()))
+    {
+        return;
+    }
+
+    // Start on level 1, or continue a saved run on its level as it was saved. Inserted as
+    // the run starts, so the level spawns as the change is seen in the first timestep
+    let level: CurrentLevel = match snapshot {
+        Some(snapshot) => snapshot.current_level(),
+        None => level_get(1, &asset_server, &levels).unwrap_or_else(|| {
+            Error::new(
+                ErrorLevel::Warning,
+                "Unable to load level 1, using the built-in level",
+                None,
+            );
+            CurrentLevel::default()
+        }),
+    };
+    commands.insert_resource(level);
+
+    commands.set_state(AppState::InGame);
+}
+
+/// Switch to the level for each level up, keeping the current layout when there isn't one
+pub fn level_advance(
+    mut commands: Commands,
+    mut level_ups: EventReader<LevelUp>,
+    asset_server: Res<AssetServer>,
+    levels: Res<Assets<LevelDefinition>>,
+) {
+    let Some(level_up) = level_ups.read().last() else {
+        return;
+    };
+
+    match level_get(level_up.level, &asset_server, &levels) {
+        Some(level) => commands.insert_resource(level),
+        None => {
+            Error::new(
+                ErrorLevel::Info,
+                &format!(
+                    "No level {} to load, keeping the current layout",
+                    level_up.level
+                ),
+                None,
+            );
+        }
+    }
+}
+
+/// Get a loaded level by number
+fn level_get(
+    number: u32,
+    asset_server: &AssetServer,
+    levels: &Assets<LevelDefinition>,
+) -> Option<CurrentLevel> {
+    let handle: Handle<LevelDefinition> = asset_server.get_handle(LevelDefinition::path(number))?;
+    let definition: LevelDefinition = levels.get(&handle)?.clone();
+
+    Some(CurrentLevel {
+        number,
+        definition,
+        handle,
+    })
+}
+
+/// Pick up changes to the current level's file
+pub fn level_reload(
+    mut events: EventReader<AssetEvent<LevelDefinition>>,
+    mut level: ResMut<CurrentLevel>,
+    levels: Res<Assets<LevelDefinition>>,
+) {
+    for event in events.read() {
+        if !event.is_modified(&level.handle) {
+            continue;
+        }
+        if let Some(definition) = levels.get(&level.handle) {
+            info!("Reloaded level {}: {}", level.number, definition.name);
+            level.definition = definition.clone();
+        }
+    }
+}
+
+/// Replace the level entities with those of the current level
+pub fn level_spawn(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    entities: Query<Entity, With<LevelEntity>>,
+    level: Res<CurrentLevel>,
+    game_time: Res<GameTime>,
+) {
+    // Clear the previous level
+    for entity in &entities {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    // Spawn the level
+    let definition: &LevelDefinition = &level.definition;
+    spawn_obstacles(
+        &mut commands,
+        &mut meshes,
+        &mut materials,
+        definition,
+        game_time.get(),
+    );
+    spawn_pickups(
+        &mut commands,
+        &mut meshes,
+        &mut materials,
+        definition,
+        game_time.get(),
+    );
+
+    info!("Level {}: {}", level.number, definition.name);
+}
+
+/// Place the camera and set the lighting for the current level
+pub fn level_view(
+    level: Res<CurrentLevel>,
+    tuning: Res<Tuning>,
+    mut cameras: Query<(&mut Transform, &mut CameraController), Without<Player>>,
+    players: Query<&Transform, With<Player>>,
+    mut lights: Query<&mut DirectionalLight>,
+    mut ambient_light: ResMut<AmbientLight>,
+) {
+    let definition: &LevelDefinition = &level.definition;
+
+    // Place the camera, starting from the player
+    let start: Vec2 = players
+        .get_single()
+        .map_or(definition.spawn_point, |transform| {
+            transform.translation.truncate()
+        });
+    for (mut transform, mut controller) in &mut cameras {
+        *controller = CameraController::new(&definition.camera, definition.extents.rect(), start);
+        *transform = controller.transform();
+    }
+
+    // Set the lighting
+    let intensity: f32 = tuning.lighting.intensity;
+    for mut light in &mut lights {
+        light.illuminance = definition.lighting.illuminance * intensity;
+        light.shadows_enabled = tuning.lighting.shadows_enabled;
+        light.shadow_depth_bias = tuning.lighting.shadow_depth_bias;
+        light.shadow_normal_bias = tuning.lighting.shadow_normal_bias;
+    }
+    ambient_light.brightness = definition.lighting.ambient_brightness * intensity;
+}
+
+/// Play the current level's camera intro, easing to its field of view
+pub fn level_intro(
+    level: Res<CurrentLevel>,
+    mut zooms: EventWriter<CameraZoom>,
+    mut moves: EventWriter<CameraMove>,
+) {
+    let camera: &LevelCamera = &level.definition.camera;
+
+    let _event_id = zooms.send(CameraZoom {
+        fov: camera.fov.to_radians(),
+        duration: LEVEL_ZOOM_SECONDS,
+        ease: EaseFunction::SineInOut,
+    });
+    if let Some(intro) = &camera.intro {
+        let _event_id = moves.send(CameraMove(intro.clone()));
+    }
+}
+
+/// Move the player to the current level's spawn point
+pub fn level_spawn_point(
+    level: Res<CurrentLevel>,
+    mut players: Query<
+        (
+            &mut SpawnPoint,
+            &mut Transform,
+            &mut Velocity,
+            &mut Interpolated,
+        ),
+        With<Player>,
+    >,
+) {
+    for (mut spawn_point, mut transform, mut velocity, mut interpolated) in &mut players {
+        spawn_point.0 = level.definition.spawn_point;
+        transform.translation = spawn_point.0.extend(transform.translation.z);
+        velocity.set(Vec2::ZERO);
+        interpolated.teleport(transform.translation);
+    }
+}
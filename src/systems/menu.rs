@@ -3,19 +3,24 @@ use bevy::prelude::*;
 // Modules
 use crate::{
     components::{
-        button::{ButtonIndex, IngameMenuButton, StartMenuButton},
-        menu::{IngameMenu, Menu, MenuSelection, StartMenu},
+        button::{
+            BindingsMenuButton, GameOverMenuButton, IngameMenuButton, SettingsMenuButton,
+            StartMenuButton,
+        },
+        menu::{BindingsMenu, GameOverMenu, IngameMenu, Menu, Rebinding, SettingsMenu, StartMenu},
     },
+    resources::{Action, GameSettings, InputBindings, SettingsState},
+    systems::save::{run_continue, run_save},
     AppState, PauseState,
 };
 
 /// Handle the pause toggle
 pub fn pause_toggle(
-    keys: Res<ButtonInput<KeyCode>>,
+    actions: Res<ButtonInput<Action>>,
     pause: Res<State<PauseState>>,
     mut next_pause: ResMut<NextState<PauseState>>,
 ) {
-    if keys.just_pressed(KeyCode::Escape) {
+    if actions.just_pressed(Action::Pause) {
         match pause.get() {
             PauseState::Running => next_pause.set(PauseState::Paused),
             PauseState::Paused => next_pause.set(PauseState::Running),
@@ -26,74 +31,21 @@ pub fn pause_toggle(
 impl Menu for StartMenu {
     type MenuEntity = StartMenu;
     type MenuButton = StartMenuButton;
-    type State = AppState;
-
-    /// Handle keyboard input for the main menu
-    fn keyboard_input(
-        keyboard_input: Res<ButtonInput<KeyCode>>,
-        mut selection: ResMut<MenuSelection>,
-        mut next_state: ResMut<NextState<Self::State>>,
-        mut exit: EventWriter<AppExit>,
-    ) {
-        match keyboard_input.get_pressed().next() {
-            // Move up
-            Some(KeyCode::KeyW) | Some(KeyCode::ArrowUp) => {
-                let index: usize = selection.index().saturating_sub(1);
-                selection.set_index(index);
-            }
-            // Move down
-            Some(KeyCode::KeyS) | Some(KeyCode::ArrowDown) => {
-                let index: usize = (selection.index() + 1).min(1);
-                selection.set_index(index);
-            }
-            // Select
-            Some(KeyCode::Enter) | Some(KeyCode::NumpadEnter) => {
-                // Determine the selected button
-                selection
-                    .try_into()
-                    .map(|selected_button| match selected_button {
-                        // Start game
-                        StartMenuButton::Start => next_state.set(Self::State::InGame),
-                        // Quit game
-                        StartMenuButton::Quit => {
-                            let _event_id = exit.send(AppExit::Success);
-                        }
-                    })
-                    // Errors logged automatically within try_into
-                    .ok();
-            }
-            _ => {
-                debug!(
-                    "Unhandled keyboard input: {:?}",
-                    keyboard_input.get_pressed().next()
-                );
-            }
-        }
-    }
 
-    /// Handle mouse input for the main menu
-    fn mouse_input(
-        mut interactions: Query<(&Interaction, &StartMenuButton), Changed<Interaction>>,
-        mut selection: ResMut<MenuSelection>,
-        mut next_state: ResMut<NextState<Self::State>>,
-        mut exit: EventWriter<AppExit>,
-    ) {
-        for (interaction, button) in &mut interactions {
-            // Check the mouse interaction type
-            match *interaction {
-                Interaction::Pressed => match button {
-                    // Start game
-                    StartMenuButton::Start => next_state.set(AppState::InGame),
-                    // Quit game
-                    StartMenuButton::Quit => {
-                        let _event_id = exit.send(AppExit::Success);
-                    }
-                },
-                Interaction::Hovered => {
-                    // Set the selection index
-                    selection.set_index(button.index());
-                }
-                Interaction::None => {}
+    const BACK_HIGHLIGHT: Option<StartMenuButton> = Some(StartMenuButton::Quit);
+
+    /// Handle a selected main menu button
+    fn select(commands: &mut Commands, button: StartMenuButton) {
+        match button {
+            // Continue the saved run
+            StartMenuButton::Continue => commands.run_system_cached(run_continue),
+            // Start game
+            StartMenuButton::Start => commands.set_state(AppState::Loading),
+            // Open the settings
+            StartMenuButton::Settings => commands.set_state(SettingsState::Open),
+            // Quit game
+            StartMenuButton::Quit => {
+                commands.send_event(AppExit::Success);
             }
         }
     }
@@ -102,71 +54,88 @@ impl Menu for StartMenu {
 impl Menu for IngameMenu {
     type MenuEntity = IngameMenu;
     type MenuButton = IngameMenuButton;
-    type State = PauseState;
-
-    /// Handle keyboard input for the in-game menu
-    fn keyboard_input(
-        keys: Res<ButtonInput<KeyCode>>,
-        mut selection: ResMut<MenuSelection>,
-        mut next_pause: ResMut<NextState<PauseState>>,
-        mut exit: EventWriter<AppExit>,
-    ) {
-        match keys.get_pressed().next() {
-            // Move up
-            Some(KeyCode::KeyW) | Some(KeyCode::ArrowUp) => {
-                let index: usize = selection.index().saturating_sub(1);
-                selection.set_index(index);
-            }
-            // Move down
-            Some(KeyCode::KeyS) | Some(KeyCode::ArrowDown) => {
-                let index: usize = (selection.index() + 1).min(1);
-                selection.set_index(index);
-            }
-            // Select
-            Some(KeyCode::Enter) | Some(KeyCode::NumpadEnter) => {
-                // Determine the selected button
-                selection
-                    .try_into()
-                    .map(|selected_button| match selected_button {
-                        // Resume game
-                        IngameMenuButton::Resume => next_pause.set(PauseState::Running),
-                        // Quit game
-                        IngameMenuButton::Quit => {
-                            let _event_id = exit.send(AppExit::Success);
-                        }
-                    })
-                    .ok();
-            }
-            _ => {
-                debug!("Unhandled keyboard input: {:?}", keys.get_pressed().next());
+
+    const BACK_BUTTON: Option<IngameMenuButton> = Some(IngameMenuButton::Resume);
+
+    /// Handle a selected in-game menu button
+    fn select(commands: &mut Commands, button: IngameMenuButton) {
+        match button {
+            // Resume game
+            IngameMenuButton::Resume => commands.set_state(PauseState::Running),
+            // Save the run
+            IngameMenuButton::Save => commands.run_system_cached(run_save),
+            // Open the settings
+            IngameMenuButton::Settings => commands.set_state(SettingsState::Open),
+            // Return to the main menu
+            IngameMenuButton::MainMenu => commands.set_state(AppState::Menu),
+            // Quit game
+            IngameMenuButton::Quit => {
+                commands.send_event(AppExit::Success);
             }
         }
     }
+}
+
+impl Menu for GameOverMenu {
+    type MenuEntity = GameOverMenu;
+    type MenuButton = GameOverMenuButton;
 
-    /// Handle mouse input for the in-game menu
-    fn mouse_input(
-        mut interactions: Query<(&Interaction, &IngameMenuButton), Changed<Interaction>>,
-        mut selection: ResMut<MenuSelection>,
-        mut next_pause: ResMut<NextState<PauseState>>,
-        mut exit: EventWriter<AppExit>,
-    ) {
-        for (interaction, button) in &mut interactions {
-            // Check the mouse interaction type
-            match *interaction {
-                Interaction::Pressed => match button {
-                    // Resume game
-                    IngameMenuButton::Resume => next_pause.set(PauseState::Running),
-                    // Quit game
-                    IngameMenuButton::Quit => {
-                        let _event_id = exit.send(AppExit::Success);
-                    }
-                },
-                Interaction::Hovered => {
-                    // Set the selection index
-                    selection.set_index(button.index());
-                }
-                Interaction::None => {}
+    const TITLE: Option<&'static str> = Some("Game Over");
+    const BACK_HIGHLIGHT: Option<GameOverMenuButton> = Some(GameOverMenuButton::MainMenu);
+
+    /// Handle a selected game over menu button
+    fn select(commands: &mut Commands, button: GameOverMenuButton) {
+        match button {
+            // Start a new run
+            GameOverMenuButton::Restart => commands.set_state(AppState::Loading),
+            // Return to the main menu
+            GameOverMenuButton::MainMenu => commands.set_state(AppState::Menu),
+            // Quit game
+            GameOverMenuButton::Quit => {
+                commands.send_event(AppExit::Success);
             }
         }
     }
 }
+
+impl Menu for SettingsMenu {
+    type MenuEntity = SettingsMenu;
+    type MenuButton = SettingsMenuButton;
+
+    const TITLE: Option<&'static str> = Some("Settings");
+    const BACK_BUTTON: Option<SettingsMenuButton> = Some(SettingsMenuButton::Back);
+
+    /// Handle a selected settings menu button
+    fn select(commands: &mut Commands, button: SettingsMenuButton) {
+        match button {
+            // Open the key bindings
+            SettingsMenuButton::Controls => commands.set_state(SettingsState::Bindings),
+            // Close the settings
+            SettingsMenuButton::Back => commands.set_state(SettingsState::Closed),
+            // Step the setting, wrapping around so it can be cycled with one button
+            _ => commands.queue(move |world: &mut World| {
+                button.adjust(&mut world.resource_mut::<GameSettings>(), true, true);
+            }),
+        }
+    }
+}
+
+impl Menu for BindingsMenu {
+    type MenuEntity = BindingsMenu;
+    type MenuButton = BindingsMenuButton;
+
+    const TITLE: Option<&'static str> = Some("Controls");
+    const BACK_BUTTON: Option<BindingsMenuButton> = Some(BindingsMenuButton::Back);
+
+    /// Handle a selected key bindings menu button
+    fn select(commands: &mut Commands, button: BindingsMenuButton) {
+        match button {
+            // Wait for a key to bind to the action
+            BindingsMenuButton::Bind(action) => commands.insert_resource(Rebinding::new(action)),
+            // Restore the default bindings
+            BindingsMenuButton::Reset => commands.insert_resource(InputBindings::default()),
+            // Return to the settings
+            BindingsMenuButton::Back => commands.set_state(SettingsState::Open),
+        }
+    }
+}
@@ -1,4 +1,20 @@
+pub mod audio;
 pub mod camera;
+pub mod collision;
+pub mod headless;
+pub mod hud;
+pub mod input;
+pub mod level;
 pub mod menu;
+pub mod movement;
+pub mod obstacle;
+pub mod pickup;
 pub mod player;
+pub mod progression;
+pub mod replay;
+pub mod rng;
+pub mod save;
+pub mod settings;
+pub mod state;
 pub mod time;
+pub mod tuning;
@@ -0,0 +1,187 @@
+use bevy::prelude::*;
+
+// Modules
+use crate::components::movement::{Acceleration, Interpolated, Movement, Velocity};
+
+/// Integrate acceleration, friction and drag into velocity, then velocity into position
+pub fn apply_velocity(
+    mut bodies: Query<(
+        &mut Transform,
+        &mut Velocity,
+        Option<&Acceleration>,
+        Option<&Movement>,
+    )>,
+    time: Res<Time>,
+) {
+    let delta: f32 = time.delta_secs();
+
+    for (mut transform, mut velocity, acceleration, movement) in &mut bodies {
+        let mut current: Vec2 = velocity.get();
+
+        // Entities without movement tuning keep a constant velocity
+        if let Some(movement) = movement {
+            let acceleration: Vec2 = acceleration.map(Acceleration::get).unwrap_or(Vec2::ZERO);
+
+            if acceleration != Vec2::ZERO {
+                // Accelerate in the input direction
+                current += acceleration * delta;
+            } else {
+                // Slow down while coasting
+                let speed: f32 = (current.length() - movement.friction * delta).max(0.0);
+                current = current.normalize_or_zero() * speed;
+            }
+
+            // Apply drag in proportion to speed
+            current /= 1.0 + movement.drag * delta;
+
+            // Limit the speed
+            current = current.clamp_length_max(movement.max_speed * movement.speed_multiplier);
+        }
+
+        // Move the entity
+        velocity.set(current);
+        transform.translation += current.extend(0.0) * delta;
+    }
+}
+
+/// Put back the simulated positions before the fixed timesteps run
+pub fn interpolation_restore(mut bodies: Query<(&mut Transform, &Interpolated)>) {
+    for (mut transform, interpolated) in &mut bodies {
+        interpolated.restore(&mut transform.translation);
+    }
+}
+
+/// Record the simulated positions before each fixed timestep
+pub fn interpolation_store(mut bodies: Query<(&Transform, &mut Interpolated)>) {
+    for (transform, mut interpolated) in &mut bodies {
+        interpolated.store(transform.translation);
+    }
+}
+
+/// Render each position part way between the last two fixed timesteps
+pub fn interpolation_apply(
+    mut bodies: Query<(&mut Transform, &mut Interpolated)>,
+    time: Res<Time<Fixed>>,
+) {
+    let fraction: f32 = time.overstep_fraction();
+
+    for (mut transform, mut interpolated) in &mut bodies {
+        interpolated.apply(&mut transform.translation, fraction);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+    use std::time::Duration;
+
+    // Modules
+    use super::*;
+    use crate::systems::time::FIXED_TIMESTEP_HZ;
+
+    /// Length of a fixed timestep, in seconds
+    const DELTA: f32 = 1.0 / FIXED_TIMESTEP_HZ as f32;
+
+    /// Spawn a body with movement tuning, accelerating and already moving
+    fn spawn(world: &mut World, acceleration: Vec2, velocity: Vec2) -> Entity {
+        let mut body_velocity = Velocity::default();
+        body_velocity.set(velocity);
+        let mut body_acceleration = Acceleration::default();
+        body_acceleration.set(acceleration);
+
+        world
+            .spawn((
+                Transform::default(),
+                body_velocity,
+                body_acceleration,
+                Movement::default(),
+            ))
+            .id()
+    }
+
+    /// Run a number of fixed timesteps, then get the body's velocity
+    fn step(world: &mut World, body: Entity, ticks: u32) -> Vec2 {
+        for _ in 0..ticks {
+            let mut time: Time = Time::default();
+            time.advance_by(Duration::from_secs_f64(1.0 / FIXED_TIMESTEP_HZ));
+            world.insert_resource(time);
+            assert!(world.run_system_once(apply_velocity).is_ok());
+        }
+
+        world.get::<Velocity>(body).map_or(Vec2::NAN, Velocity::get)
+    }
+
+    #[test]
+    fn acceleration_is_slowed_by_drag() {
+        let mut world = World::new();
+        let movement = Movement::default();
+        let body: Entity = spawn(
+            &mut world,
+            Vec2::new(movement.acceleration, 0.0),
+            Vec2::ZERO,
+        );
+
+        let velocity: Vec2 = step(&mut world, body, 1);
+        let expected: f32 = movement.acceleration * DELTA / (1.0 + movement.drag * DELTA);
+        assert_eq!(velocity, Vec2::new(expected, 0.0));
+
+        // Moved by the new velocity
+        let translation: Vec3 = world
+            .get::<Transform>(body)
+            .map_or(Vec3::NAN, |transform| transform.translation);
+        assert_eq!(translation, Vec3::new(expected * DELTA, 0.0, 0.0));
+    }
+
+    #[test]
+    fn friction_applies_only_while_coasting() {
+        let mut world = World::new();
+        let movement = Movement::default();
+        let drag: f32 = 1.0 + movement.drag * DELTA;
+
+        // Coasting loses speed to friction, then drag
+        let coasting: Entity = spawn(&mut world, Vec2::ZERO, Vec2::new(4.0, 0.0));
+        let velocity: Vec2 = step(&mut world, coasting, 1);
+        assert_eq!(velocity.x, (4.0 - movement.friction * DELTA) / drag);
+
+        // Accelerating sideways keeps the speed, losing only drag
+        let steering: Entity = spawn(&mut world, Vec2::new(0.0, 1.0), Vec2::new(4.0, 0.0));
+        let velocity: Vec2 = step(&mut world, steering, 1);
+        assert_eq!(velocity.x, 4.0 / drag);
+
+        // Coasting stops without reversing
+        let stopping: Entity = spawn(&mut world, Vec2::ZERO, Vec2::new(0.1, 0.0));
+        assert_eq!(step(&mut world, stopping, 1), Vec2::ZERO);
+    }
+
+    #[test]
+    fn speed_is_limited_by_the_boosted_top_speed() {
+        let mut world = World::new();
+        let movement = Movement::default();
+        let body: Entity = spawn(&mut world, Vec2::new(1.0, 1.0) * 1000.0, Vec2::ZERO);
+
+        let velocity: Vec2 = step(&mut world, body, 64);
+        assert!((velocity.length() - movement.max_speed).abs() < 1e-4);
+
+        // Boosts raise the limit
+        world.entity_mut(body).insert(Movement {
+            speed_multiplier: 1.5,
+            ..movement
+        });
+        let velocity: Vec2 = step(&mut world, body, 64);
+        assert!((velocity.length() - movement.max_speed * 1.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn bodies_without_movement_keep_their_velocity() {
+        let mut world = World::new();
+        let body: Entity = world
+            .spawn((Transform::default(), Velocity { x: 1.0, y: -2.0 }))
+            .id();
+
+        assert_eq!(step(&mut world, body, 4), Vec2::new(1.0, -2.0));
+        let translation: Vec3 = world
+            .get::<Transform>(body)
+            .map_or(Vec3::NAN, |transform| transform.translation);
+        assert_eq!(translation, Vec3::new(4.0, -8.0, 0.0) * DELTA);
+    }
+}
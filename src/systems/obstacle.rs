@@ -0,0 +1,255 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+// Modules
+use crate::{
+    components::{
+        collision::{Collider, Solid},
+        level::LevelEntity,
+        movement::{Interpolated, Velocity},
+        obstacle::{Hazard, HazardSpawner, Lifetime, Patrol, Wall},
+        player::Player,
+    },
+    events::{CollisionStart, CollisionStay, Damage},
+    level::LevelDefinition,
+    resources::{AppState, GameRng, GameState, GameTime},
+};
+
+// Obstacle constants
+const WALL_DEPTH: f32 = 1.0;
+const HAZARD_RADIUS: f32 = 0.3;
+
+/// Shared mesh and material for spawned hazards
+#[derive(Resource)]
+pub struct HazardAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+/// Spawn a level's walls, patrolling hazards and hazard spawners
+pub fn spawn_obstacles(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    level: &LevelDefinition,
+    seconds: f32,
+) {
+    // Create the wall material
+    let wall_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.4, 0.4, 0.45),
+        perceptual_roughness: 0.9,
+        ..default()
+    });
+
+    // Spawn the walls
+    for wall in &level.walls {
+        commands.spawn((
+            Mesh3d(meshes.add(Cuboid::new(wall.size.x, wall.size.y, WALL_DEPTH))),
+            MeshMaterial3d(wall_material.clone()),
+            Transform::from_translation(wall.position.extend(0.0)),
+            Wall,
+            Solid,
+            Collider::aabb(wall.size),
+            LevelEntity,
+            StateScoped(AppState::InGame),
+        ));
+    }
+
+    // Create the hazard assets, shared with the spawners
+    let hazard_assets = HazardAssets {
+        mesh: meshes.add(Sphere::new(HAZARD_RADIUS)),
+        material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.85, 0.2, 0.2),
+            emissive: LinearRgba::rgb(0.4, 0.0, 0.0),
+            ..default()
+        }),
+    };
+
+    // Spawn the patrolling hazards at their first waypoint
+    for patrol in &level.patrols {
+        let start: Vec2 = patrol.points.first().copied().unwrap_or_default();
+        hazard_assets.spawn_patrol(
+            commands,
+            start,
+            Patrol::new(patrol.points.clone(), patrol.speed),
+        );
+    }
+
+    // Spawn the hazard spawners
+    for spawner in &level.spawners {
+        spawn_spawner(
+            commands,
+            spawner.position,
+            HazardSpawner {
+                interval: spawner.interval,
+                velocity: spawner.velocity,
+                lifetime: spawner.lifetime,
+                spread: spawner.spread,
+                next_spawn: seconds + spawner.interval,
+            },
+        );
+    }
+
+    commands.insert_resource(hazard_assets);
+}
+
+impl HazardAssets {
+    /// Spawn a hazard patrolling between waypoints
+    pub fn spawn_patrol(&self, commands: &mut Commands, position: Vec2, patrol: Patrol) {
+        commands.spawn((
+            Mesh3d(self.mesh.clone()),
+            MeshMaterial3d(self.material.clone()),
+            Transform::from_translation(position.extend(0.0)),
+            Hazard::default(),
+            Collider::sphere(HAZARD_RADIUS),
+            Velocity::default(),
+            Interpolated::default(),
+            patrol,
+            LevelEntity,
+            StateScoped(AppState::InGame),
+        ));
+    }
+
+    /// Spawn a hazard that travels in a straight line until it expires
+    pub fn spawn_hazard(
+        &self,
+        commands: &mut Commands,
+        position: Vec2,
+        velocity: Vec2,
+        lifetime: Lifetime,
+    ) {
+        commands.spawn((
+            Mesh3d(self.mesh.clone()),
+            MeshMaterial3d(self.material.clone()),
+            Transform::from_translation(position.extend(0.0)),
+            Hazard::default(),
+            Collider::sphere(HAZARD_RADIUS),
+            Velocity {
+                x: velocity.x,
+                y: velocity.y,
+            },
+            Interpolated::default(),
+            lifetime,
+            LevelEntity,
+            StateScoped(AppState::InGame),
+        ));
+    }
+}
+
+/// Spawn an invisible hazard spawner
+pub fn spawn_spawner(commands: &mut Commands, position: Vec2, spawner: HazardSpawner) {
+    commands.spawn((
+        Transform::from_translation(position.extend(0.0)),
+        spawner,
+        LevelEntity,
+        StateScoped(AppState::InGame),
+    ));
+}
+
+/// Steer patrolling entities towards their next waypoint
+pub fn hazard_patrol(
+    mut patrols: Query<(&Transform, &mut Velocity, &mut Patrol)>,
+    game_state: Res<GameState>,
+    time: Res<Time>,
+) {
+    for (transform, mut velocity, mut patrol) in &mut patrols {
+        let position: Vec2 = transform.translation.truncate();
+        let speed: f32 = patrol.speed * game_state.difficulty();
+
+        // Turn towards the next waypoint once this one is reached
+        if patrol
+            .target()
+            .is_some_and(|target| position.distance(target) <= speed * time.delta_secs())
+        {
+            patrol.advance();
+        }
+
+        // Head towards the waypoint
+        let direction: Vec2 = patrol
+            .target()
+            .map(|target| (target - position).normalize_or_zero())
+            .unwrap_or(Vec2::ZERO);
+        velocity.set(direction * speed);
+    }
+}
+
+/// Emit hazards from each spawner, faster and more often at higher levels, turned at random
+/// within the spawner's spread
+pub fn hazard_spawn(
+    mut commands: Commands,
+    mut spawners: Query<(&Transform, &mut HazardSpawner)>,
+    hazard_assets: Res<HazardAssets>,
+    game_state: Res<GameState>,
+    game_time: Res<GameTime>,
+    mut rng: ResMut<GameRng>,
+) {
+    let difficulty: f32 = game_state.difficulty();
+
+    for (transform, mut spawner) in &mut spawners {
+        if game_time.get() < spawner.next_spawn {
+            continue;
+        }
+        spawner.next_spawn = game_time.get() + spawner.interval / difficulty;
+
+        // Turn the hazard, drawing only for spawners with a spread
+        let turn: f32 = match spawner.spread > 0.0 {
+            true => rng
+                .stream(GameRng::SPAWNING)
+                .gen_range(-spawner.spread..=spawner.spread),
+            false => 0.0,
+        };
+
+        // Spawn the hazard
+        hazard_assets.spawn_hazard(
+            &mut commands,
+            transform.translation.truncate(),
+            Vec2::from_angle(turn).rotate(spawner.velocity * difficulty),
+            Lifetime {
+                expires: game_time.get() + spawner.lifetime,
+            },
+        );
+    }
+}
+
+/// Despawn entities whose lifetime has passed
+pub fn lifetime_expire(
+    mut commands: Commands,
+    entities: Query<(Entity, &Lifetime)>,
+    game_time: Res<GameTime>,
+) {
+    for (entity, lifetime) in &entities {
+        if game_time.get() >= lifetime.expires {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Damage the player while they touch a hazard
+pub fn hazard_hit(
+    mut started: EventReader<CollisionStart>,
+    mut stayed: EventReader<CollisionStay>,
+    mut damage: EventWriter<Damage>,
+    players: Query<(), With<Player>>,
+    hazards: Query<&Hazard>,
+) {
+    // Include ongoing contacts, so the player is hurt again once invulnerability ends
+    let contacts = started
+        .read()
+        .map(|collision| (collision.a, collision.b))
+        .chain(stayed.read().map(|collision| (collision.a, collision.b)));
+
+    for (a, b) in contacts {
+        // Find the player and the hazard they touched
+        let (player, hazard) = match (players.contains(a), players.contains(b)) {
+            (true, false) => (a, b),
+            (false, true) => (b, a),
+            _ => continue,
+        };
+        if let Ok(hazard) = hazards.get(hazard) {
+            let _event_id = damage.send(Damage {
+                target: player,
+                amount: hazard.damage,
+            });
+        }
+    }
+}
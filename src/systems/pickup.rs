@@ -0,0 +1,327 @@
+use bevy::prelude::*;
+
+// Modules
+use crate::{
+    components::{
+        collision::Collider,
+        level::LevelEntity,
+        movement::Movement,
+        pickup::{Pickup, PowerUps},
+        player::Player,
+    },
+    events::{CollisionStart, PickupCollected, ScoreEvent},
+    level::{LevelDefinition, PickupDefinition},
+    resources::{AppState, GameTime},
+};
+
+// Pickup constants
+const PICKUP_RADIUS: f32 = 0.25;
+const PICKUP_INTERVAL: f32 = 3.0;
+const PICKUP_LIMIT: usize = 6;
+const PICKUP_OPENING: usize = 3;
+const SPEED_BOOST: f32 = 1.5;
+const SCORE_MULTIPLIER: u32 = 2;
+
+/// Shared mesh for the pickups, and the next pickup to spawn
+#[derive(Resource)]
+pub struct PickupSchedule {
+    mesh: Handle<Mesh>,
+    rotation: Vec<PickupDefinition>,
+    /// Game time of the next pickup
+    pub next_spawn: f32,
+    /// Position in the rotation of the next pickup
+    pub index: usize,
+}
+
+/// Setup a level's pickup schedule, with the first few pickups already spawned
+pub fn spawn_pickups(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    level: &LevelDefinition,
+    seconds: f32,
+) {
+    let mut schedule = PickupSchedule {
+        mesh: meshes.add(Sphere::new(PICKUP_RADIUS)),
+        rotation: level.pickups.clone(),
+        next_spawn: seconds + PICKUP_INTERVAL,
+        index: 0,
+    };
+
+    // Spawn the opening pickups
+    for _ in 0..PICKUP_OPENING.min(schedule.rotation.len()) {
+        schedule.spawn_next(commands, materials);
+    }
+
+    commands.insert_resource(schedule);
+}
+
+/// Spawn the next pickup in the rotation on a schedule, measured in game time
+pub fn pickup_spawn(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut schedule: ResMut<PickupSchedule>,
+    pickups: Query<(), With<Pickup>>,
+    game_time: Res<GameTime>,
+) {
+    if game_time.get() < schedule.next_spawn {
+        return;
+    }
+    schedule.next_spawn = game_time.get() + PICKUP_INTERVAL;
+
+    // Don't crowd the play area
+    if pickups.iter().count() < PICKUP_LIMIT {
+        schedule.spawn_next(&mut commands, &mut materials);
+    }
+}
+
+impl PickupSchedule {
+    /// Spawn the next pickup in the rotation
+    fn spawn_next(&mut self, commands: &mut Commands, materials: &mut Assets<StandardMaterial>) {
+        let Some(&pickup) = self.rotation.get(self.index % self.rotation.len().max(1)) else {
+            return;
+        };
+        self.index += 1;
+
+        self.spawn(commands, materials, pickup);
+    }
+
+    /// Spawn a pickup
+    pub fn spawn(
+        &self,
+        commands: &mut Commands,
+        materials: &mut Assets<StandardMaterial>,
+        pickup: PickupDefinition,
+    ) {
+        commands.spawn((
+            Mesh3d(self.mesh.clone()),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: pickup.kind.colour(),
+                emissive: pickup.kind.colour().to_linear() * 0.3,
+                ..default()
+            })),
+            Transform::from_translation(pickup.position.extend(0.0)),
+            pickup.kind,
+            Collider::sphere(PICKUP_RADIUS),
+            LevelEntity,
+            StateScoped(AppState::InGame),
+        ));
+    }
+}
+
+/// Collect the pickups the player touches
+pub fn pickup_collect(
+    mut commands: Commands,
+    mut collisions: EventReader<CollisionStart>,
+    mut collected: EventWriter<PickupCollected>,
+    players: Query<(), With<Player>>,
+    pickups: Query<&Pickup>,
+) {
+    for collision in collisions.read() {
+        // Find the player and the pickup they touched
+        let (player, entity) = match (players.contains(collision.a), players.contains(collision.b))
+        {
+            (true, false) => (collision.a, collision.b),
+            (false, true) => (collision.b, collision.a),
+            _ => continue,
+        };
+        let Ok(pickup) = pickups.get(entity) else {
+            continue;
+        };
+
+        commands.entity(entity).despawn_recursive();
+        let _event_id = collected.send(PickupCollected {
+            player,
+            pickup: *pickup,
+        });
+    }
+}
+
+/// Score collected pickups and activate their power-ups
+pub fn pickup_apply(
+    mut collected: EventReader<PickupCollected>,
+    mut scores: EventWriter<ScoreEvent>,
+    mut players: Query<&mut PowerUps, With<Player>>,
+    game_time: Res<GameTime>,
+) {
+    for PickupCollected { player, pickup } in collected.read() {
+        let Ok(mut power_ups) = players.get_mut(*player) else {
+            continue;
+        };
+
+        // Score the pickup, doubled by the score multiplier
+        let multiplier: u32 = match power_ups.active(Pickup::ScoreMultiplier, game_time.get()) {
+            true => SCORE_MULTIPLIER,
+            false => 1,
+        };
+        if pickup.points() > 0 {
+            let _event_id = scores.send(ScoreEvent {
+                points: pickup.points() * multiplier,
+            });
+        }
+
+        // Start the power-up
+        if let Some(duration) = pickup.duration() {
+            power_ups.activate(*pickup, game_time.get() + duration);
+        }
+    }
+}
+
+/// Apply the active power-ups that change the player's movement
+pub fn power_up_effects(
+    mut players: Query<(&PowerUps, &mut Movement), With<Player>>,
+    game_time: Res<GameTime>,
+) {
+    for (power_ups, mut movement) in &mut players {
+        let speed_multiplier: f32 = match power_ups.active(Pickup::SpeedBoost, game_time.get()) {
+            true => SPEED_BOOST,
+            false => 1.0,
+        };
+
+        // Only write on change, to keep change detection meaningful
+        if movement.speed_multiplier != speed_multiplier {
+            movement.speed_multiplier = speed_multiplier;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        events::CollisionStay,
+        resources::{Collisions, GameState, SpatialHash},
+        systems::{collision::collision_detect, progression::score_update},
+    };
+
+    /// Build an app that collects and applies pickups every update
+    fn app() -> (App, Entity) {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<SpatialHash>()
+            .init_resource::<Collisions>()
+            .init_resource::<GameState>()
+            .init_resource::<GameTime>()
+            .add_event::<CollisionStart>()
+            .add_event::<CollisionStay>()
+            .add_event::<PickupCollected>()
+            .add_event::<ScoreEvent>()
+            .add_systems(
+                Update,
+                (
+                    collision_detect,
+                    pickup_collect,
+                    pickup_apply,
+                    power_up_effects,
+                    score_update,
+                )
+                    .chain(),
+            );
+
+        let player: Entity = app
+            .world_mut()
+            .spawn((
+                Player,
+                Transform::default(),
+                Collider::sphere(0.5),
+                PowerUps::default(),
+                Movement::default(),
+            ))
+            .id();
+        (app, player)
+    }
+
+    /// Spawn a pickup at a position
+    fn spawn(app: &mut App, pickup: Pickup, position: Vec2) -> Entity {
+        app.world_mut()
+            .spawn((
+                pickup,
+                Transform::from_translation(position.extend(0.0)),
+                Collider::sphere(PICKUP_RADIUS),
+            ))
+            .id()
+    }
+
+    /// Update at a game time
+    fn update_at(app: &mut App, seconds: f32) {
+        app.world_mut().resource_mut::<GameTime>().0 = seconds;
+        app.update();
+    }
+
+    /// Get the run's score
+    fn score(app: &App) -> u32 {
+        app.world().resource::<GameState>().score()
+    }
+
+    #[test]
+    fn touched_pickups_are_collected_and_scored() {
+        let (mut app, player) = app();
+        let coin: Entity = spawn(&mut app, Pickup::Coin, Vec2::new(0.5, 0.0));
+        let gem: Entity = spawn(&mut app, Pickup::Gem, Vec2::new(5.0, 0.0));
+        update_at(&mut app, 0.0);
+
+        // Only the touched pickup is collected
+        assert!(app.world().get_entity(coin).is_err());
+        assert!(app.world().get_entity(gem).is_ok());
+        let collected: Vec<PickupCollected> = app
+            .world_mut()
+            .resource_mut::<Events<PickupCollected>>()
+            .drain()
+            .collect();
+        assert_eq!(collected.len(), 1);
+        assert_eq!(
+            collected
+                .first()
+                .map(|collected| (collected.player, collected.pickup)),
+            Some((player, Pickup::Coin))
+        );
+        assert_eq!(score(&app), Pickup::Coin.points());
+
+        // Walking onto the gem scores it too
+        if let Some(mut transform) = app.world_mut().get_mut::<Transform>(player) {
+            transform.translation.x = 5.0;
+        }
+        update_at(&mut app, 1.0);
+        assert!(app.world().get_entity(gem).is_err());
+        assert_eq!(score(&app), Pickup::Coin.points() + Pickup::Gem.points());
+    }
+
+    #[test]
+    fn score_multiplier_doubles_points_until_it_expires() {
+        let (mut app, _player) = app();
+        spawn(&mut app, Pickup::ScoreMultiplier, Vec2::ZERO);
+        update_at(&mut app, 0.0);
+        assert_eq!(score(&app), 0);
+
+        // Doubled while active
+        spawn(&mut app, Pickup::Coin, Vec2::ZERO);
+        update_at(&mut app, 1.0);
+        assert_eq!(score(&app), Pickup::Coin.points() * SCORE_MULTIPLIER);
+
+        // Back to normal once it expires
+        let expires: f32 = Pickup::ScoreMultiplier.duration().unwrap_or_default();
+        spawn(&mut app, Pickup::Coin, Vec2::ZERO);
+        update_at(&mut app, expires);
+        assert_eq!(score(&app), Pickup::Coin.points() * (SCORE_MULTIPLIER + 1));
+    }
+
+    #[test]
+    fn speed_boost_lasts_its_duration() {
+        let (mut app, player) = app();
+        let speed_multiplier = |app: &App| {
+            app.world()
+                .get::<Movement>(player)
+                .map(|movement| movement.speed_multiplier)
+        };
+        spawn(&mut app, Pickup::SpeedBoost, Vec2::ZERO);
+        update_at(&mut app, 2.0);
+        assert_eq!(speed_multiplier(&app), Some(SPEED_BOOST));
+
+        // Still boosted just before it expires, then back to normal
+        let expires: f32 = 2.0 + Pickup::SpeedBoost.duration().unwrap_or_default();
+        update_at(&mut app, expires - 0.1);
+        assert_eq!(speed_multiplier(&app), Some(SPEED_BOOST));
+        update_at(&mut app, expires);
+        assert_eq!(speed_multiplier(&app), Some(1.0));
+    }
+}
@@ -2,137 +2,237 @@ use bevy::prelude::*;
 
 // Modules
 use crate::{
-    components::player::{Player, Velocity},
-    resources::GameTime,
+    components::{
+        collision::Collider,
+        movement::{Acceleration, Interpolated, Movement, Velocity},
+        pickup::{Pickup, PowerUps},
+        player::{Health, Invulnerable, Lives, Player, SpawnPoint},
+    },
+    events::{CameraShake, Damage},
+    resources::{Action, AnalogMovement, AppState, CurrentLevel, GameTime},
+    tuning::{PlayerTuning, Tuning},
 };
 
 // Player constants
-const PLAYER_SIZE: f32 = 1.0;
+const PLAYER_HEALTH: u32 = 3;
+const PLAYER_LIVES: u32 = 3;
+const INVULNERABLE_SECONDS: f32 = 1.5;
+const FLASH_RATE: f32 = 10.0;
+const DAMAGE_TRAUMA: f32 = 0.5;
 
 /// Setup and spawn the player entity
 pub fn player_setup(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    level: Res<CurrentLevel>,
+    tuning: Res<Tuning>,
 ) {
-    // Define the player cube
-    let color = Color::srgb(0.25, 0.75, 0.25);
+    let player_tuning: &PlayerTuning = &tuning.player;
 
-    // Create the cube mesh
-    let mesh = meshes.add(Cuboid::new(PLAYER_SIZE, PLAYER_SIZE, PLAYER_SIZE).mesh());
+    // Create a unit cube mesh, scaled to the tuned size
+    let mesh = meshes.add(Cuboid::new(1.0, 1.0, 1.0).mesh());
 
     // Create the material
     let material = materials.add(StandardMaterial {
-        base_color: color,
+        base_color: player_tuning.colour(),
         metallic: 0.1,
         perceptual_roughness: 0.8,
         ..default()
     });
 
-    // Position the player
-    let player_transform = Transform::from_xyz(0.0, 0.0, 0.0);
+    // Position the player at the spawn point
+    let spawn_point = SpawnPoint(level.definition.spawn_point);
+    let player_transform = Transform::from_translation(spawn_point.0.extend(0.0))
+        .with_scale(Vec3::splat(player_tuning.size));
 
     // Set the player velocity
     let player_velocity = Velocity::default();
 
+    // Set the player movement tuning
+    let player_movement = Movement::from(player_tuning);
+
     // Spawn the player
     commands.spawn((
         Mesh3d(mesh),
         MeshMaterial3d(material),
         player_transform,
         Player,
-        player_velocity,
+        (
+            player_velocity,
+            Acceleration::default(),
+            player_movement,
+            Interpolated::default(),
+        ),
+        Collider::aabb(Vec2::splat(player_tuning.size)),
+        Health::new(PLAYER_HEALTH),
+        Lives(PLAYER_LIVES),
+        Invulnerable::default(),
+        PowerUps::default(),
+        spawn_point,
+        StateScoped(AppState::InGame),
     ));
 }
 
-/// Handle player movement based on keyboard input
-pub fn player_movement(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut player_transform: Query<&mut Transform, With<Player>>,
-    camera_query: Query<(&Camera, &GlobalTransform, &Projection), With<Camera3d>>,
-    time: Res<Time>,
+/// Apply changed tuning to the player, keeping any active boosts
+pub fn player_tuning(
+    tuning: Res<Tuning>,
+    mut players: Query<(&mut Transform, &mut Movement, &mut Collider), With<Player>>,
 ) {
-    const MOVEMENT_SIZE: f32 = 1.0;
-    const MOVEMENT_SPEED: f32 = 5.0;
+    let player_tuning: &PlayerTuning = &tuning.player;
 
-    // Get the player transform
-    let mut player_transform = player_transform.single_mut();
-
-    // Init the direction
-    let mut direction = Vec3::ZERO;
-
-    // Set the direction based on the keyboard input
-    match keyboard_input.get_pressed().next() {
-        // Move up
-        Some(KeyCode::KeyW) | Some(KeyCode::ArrowUp) => direction.y += MOVEMENT_SIZE,
-        // Move down
-        Some(KeyCode::KeyS) | Some(KeyCode::ArrowDown) => direction.y -= MOVEMENT_SIZE,
-        // Move left
-        Some(KeyCode::KeyA) | Some(KeyCode::ArrowLeft) => direction.x -= MOVEMENT_SIZE,
-        // Move right
-        Some(KeyCode::KeyD) | Some(KeyCode::ArrowRight) => direction.x += MOVEMENT_SIZE,
-        // Unsupported movement
-        _ => {
-            debug!(
-                "Unhandled keyboard input: {:?}",
-                keyboard_input.get_pressed().next()
-            );
-        }
+    for (mut transform, mut movement, mut collider) in &mut players {
+        transform.scale = Vec3::splat(player_tuning.size);
+        *collider = Collider::aabb(Vec2::splat(player_tuning.size));
+        *movement = Movement {
+            speed_multiplier: movement.speed_multiplier,
+            ..Movement::from(player_tuning)
+        };
     }
+}
+
+/// Handle player movement based on the movement actions and analog stick
+pub fn player_movement(
+    actions: Res<ButtonInput<Action>>,
+    analog: Res<AnalogMovement>,
+    mut player: Query<(&mut Acceleration, &Movement), With<Player>>,
+) {
+    // Get the player acceleration
+    let (mut acceleration, movement) = player.single_mut();
+
+    // Set the direction, preferring the analog stick for finer control
+    let direction: Vec2 = match analog.get() {
+        Vec2::ZERO => movement_direction(&actions),
+        stick => stick,
+    };
+
+    // Accelerate the player
+    acceleration.set(direction * movement.acceleration * movement.speed_multiplier);
+}
+
+/// Get the movement direction from every held movement action
+fn movement_direction(actions: &ButtonInput<Action>) -> Vec2 {
+    // Check each direction
+    let up: bool = actions.pressed(Action::MoveUp);
+    let down: bool = actions.pressed(Action::MoveDown);
+    let left: bool = actions.pressed(Action::MoveLeft);
+    let right: bool = actions.pressed(Action::MoveRight);
+
+    // Combine the held directions
+    let direction = Vec2::new(movement_axis(left, right), movement_axis(down, up));
 
     // Normalize the direction to prevent faster movement diagonally
-    if direction.length() > 0.0 {
-        direction = direction.normalize();
+    direction.normalize_or_zero()
+}
+
+/// Resolve a pair of opposing directions, which cancel out when both are held
+fn movement_axis(negative: bool, positive: bool) -> f32 {
+    match (negative, positive) {
+        (true, false) => -1.0,
+        (false, true) => 1.0,
+        _ => 0.0,
     }
+}
 
-    // Move the player
-    player_transform.translation += direction * MOVEMENT_SPEED * time.delta_secs();
+/// Keep the player within the level's extents
+pub fn player_bounds(
+    level: Res<CurrentLevel>,
+    mut player: Query<(&mut Transform, &mut Velocity), With<Player>>,
+    tuning: Res<Tuning>,
+) {
+    // Get the player transform
+    let Ok((mut player_transform, mut velocity)) = player.get_single_mut() else {
+        return;
+    };
+
+    // Set the player bounds, with a margin based on the player size
+    let margin: Vec2 = Vec2::splat(tuning.player.size / 2.0);
+    let extents: Rect = level.definition.extents.rect();
+    let bounded: Vec2 = player_transform
+        .translation
+        .truncate()
+        .max(extents.min + margin)
+        .min(extents.max - margin);
+
+    // Stop the player moving into the bounds
+    let mut current: Vec2 = velocity.get();
+    if bounded.x != player_transform.translation.x {
+        current.x = 0.0;
+    }
+    if bounded.y != player_transform.translation.y {
+        current.y = 0.0;
+    }
+    velocity.set(current);
 
-    // Set the player bounds
-    player_bounds(camera_query, player_transform);
+    player_transform.translation.x = bounded.x;
+    player_transform.translation.y = bounded.y;
 }
 
-/// Set the player bounds
-fn player_bounds(
-    camera_query: Query<(&Camera, &GlobalTransform, &Projection), With<Camera3d>>,
-    mut player_transform: Mut<'_, Transform>,
+/// Apply damage to the player, respawning when their health runs out and ending the run with the last life
+pub fn player_damage(
+    mut commands: Commands,
+    mut damage: EventReader<Damage>,
+    mut players: Query<
+        (
+            &mut Health,
+            &mut Lives,
+            &mut Invulnerable,
+            &PowerUps,
+            &SpawnPoint,
+        ),
+        With<Player>,
+    >,
+    mut bodies: Query<(&mut Transform, &mut Velocity, &mut Interpolated), With<Player>>,
+    mut shakes: EventWriter<CameraShake>,
+    game_time: Res<GameTime>,
 ) {
-    if let Ok((_camera, camera_transform, projection)) = camera_query.get_single() {
-        // Get the projection to extract FOV
-        if let Projection::Perspective(perspective) = projection {
-            // Calculate the perpendicular distance from camera to the player plane
-            let camera_pos: Vec3 = camera_transform.translation();
-            let camera_forward: Dir3 = camera_transform.forward();
-            let distance_to_plane: f32 = camera_pos.z / camera_forward.z.abs();
-
-            // Calculate visible world size at the player's Z position
-            let half_height: f32 = distance_to_plane * (perspective.fov / 2.0).tan();
-            let half_width: f32 = half_height * perspective.aspect_ratio;
-
-            // Calculate the margin based on the player size
-            let margin: f32 = PLAYER_SIZE / 2.0;
-
-            // Set the player bounds
-            player_transform.translation.x = player_transform
-                .translation
-                .x
-                .clamp(-half_width + margin, half_width - margin);
-            player_transform.translation.y = player_transform
-                .translation
-                .y
-                .clamp(-half_height + margin, half_height - margin);
+    for hit in damage.read() {
+        let Ok((mut health, mut lives, mut invulnerable, power_ups, spawn_point)) =
+            players.get_mut(hit.target)
+        else {
+            continue;
+        };
+
+        // Ignore hits while invulnerable or shielded
+        if invulnerable.active(game_time.get()) || power_ups.active(Pickup::Shield, game_time.get())
+        {
+            continue;
+        }
+        invulnerable.until = game_time.get() + INVULNERABLE_SECONDS;
+
+        // Take the hit, shaking the camera
+        let _event_id = shakes.send(CameraShake {
+            trauma: DAMAGE_TRAUMA,
+        });
+        if !health.damage(hit.amount) {
+            continue;
+        }
+
+        // End the run on the last life
+        if lives.lose() == 0 {
+            commands.set_state(AppState::GameOver);
+            continue;
+        }
+
+        // Respawn at the spawn point
+        health.restore();
+        if let Ok((mut transform, mut velocity, mut interpolated)) = bodies.get_mut(hit.target) {
+            transform.translation = spawn_point.0.extend(transform.translation.z);
+            velocity.set(Vec2::ZERO);
+            interpolated.teleport(transform.translation);
         }
     }
 }
 
 /// Set up the player animation
 pub fn player_animate(
-    mut player_materials: Query<&MeshMaterial3d<StandardMaterial>, With<Player>>,
+    mut player_materials: Query<(&MeshMaterial3d<StandardMaterial>, &Invulnerable), With<Player>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     game_time: Res<GameTime>,
+    tuning: Res<Tuning>,
 ) {
     // Get the player material handle
-    let material_handle: &MeshMaterial3d<StandardMaterial> = player_materials.single_mut();
+    let (material_handle, invulnerable) = player_materials.single_mut();
 
     // Get the current game time
     let seconds: f32 = game_time.get();
@@ -141,14 +241,111 @@ pub fn player_animate(
     let intensity: f32 = (seconds.sin() + 1.0) / 2.0;
 
     // Animate the colour based on the game time
+    let (red, green, blue) = tuning.player.colour;
     let player_colour: Color = Color::srgb(
-        0.25 + intensity * 0.25,
-        0.75 - intensity * 0.25,
-        0.25 + intensity * 0.5,
+        red + intensity * 0.25,
+        green - intensity * 0.25,
+        blue + intensity * 0.5,
     );
 
+    // Flash while invulnerable
+    let flash: bool = invulnerable.active(seconds) && (seconds * FLASH_RATE).fract() < 0.5;
+
     // Update the player colour
     if let Some(material) = materials.get_mut(&material_handle.0) {
-        material.base_color = player_colour;
+        material.base_color = match flash {
+            true => Color::WHITE,
+            false => player_colour,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::input::InputPlugin;
+
+    // Modules
+    use super::*;
+    use crate::{resources::InputBindings, systems::input::update_actions};
+
+    /// Build an app that turns the held keys into player acceleration
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, InputPlugin))
+            .init_resource::<InputBindings>()
+            .init_resource::<ButtonInput<Action>>()
+            .init_resource::<AnalogMovement>()
+            .add_systems(Update, (update_actions, player_movement).chain());
+        app.world_mut()
+            .spawn((Player, Acceleration::default(), Movement::default()));
+        app
+    }
+
+    /// Hold only the given keys for one update, then get the player acceleration
+    fn accelerate(app: &mut App, keys: &[KeyCode]) -> Vec2 {
+        let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keyboard.release_all();
+        for key in keys {
+            keyboard.press(*key);
+        }
+        app.update();
+
+        app.world_mut()
+            .query_filtered::<&Acceleration, With<Player>>()
+            .single(app.world())
+            .get()
+    }
+
+    #[test]
+    fn diagonal_keys_accelerate_diagonally() {
+        let mut app = app();
+        let acceleration: f32 = Movement::default().acceleration;
+
+        let diagonal = accelerate(&mut app, &[KeyCode::KeyW, KeyCode::KeyD]);
+        assert!(diagonal.abs_diff_eq(Vec2::ONE.normalize() * acceleration, 1e-5));
+
+        // Alternative keys combine in the same way
+        let diagonal = accelerate(&mut app, &[KeyCode::ArrowDown, KeyCode::KeyA]);
+        assert!(diagonal.abs_diff_eq(Vec2::NEG_ONE.normalize() * acceleration, 1e-5));
+    }
+
+    #[test]
+    fn opposing_keys_cancel_out() {
+        let mut app = app();
+        let acceleration: f32 = Movement::default().acceleration;
+
+        let cancelled = accelerate(&mut app, &[KeyCode::KeyA, KeyCode::KeyD]);
+        assert_eq!(cancelled, Vec2::ZERO);
+
+        // The other axis still moves the player, at full speed
+        let upward = accelerate(&mut app, &[KeyCode::KeyA, KeyCode::KeyD, KeyCode::KeyW]);
+        assert_eq!(upward, Vec2::new(0.0, acceleration));
+
+        let cancelled = accelerate(
+            &mut app,
+            &[KeyCode::KeyW, KeyCode::KeyA, KeyCode::KeyS, KeyCode::KeyD],
+        );
+        assert_eq!(cancelled, Vec2::ZERO);
+    }
+
+    #[test]
+    fn every_key_combination_is_normalised() {
+        let mut app = app();
+        let acceleration: f32 = Movement::default().acceleration;
+        let keys = [KeyCode::KeyW, KeyCode::KeyA, KeyCode::KeyS, KeyCode::KeyD];
+
+        // Hold each subset of the movement keys
+        for mask in 0..(1 << keys.len()) {
+            let held: Vec<KeyCode> = (0..keys.len())
+                .filter(|index| mask & (1 << index) != 0)
+                .map(|index| keys[index])
+                .collect();
+            let length: f32 = accelerate(&mut app, &held).length();
+
+            assert!(
+                length == 0.0 || (length - acceleration).abs() < 1e-4,
+                "{held:?} accelerated at {length}"
+            );
+        }
     }
 }
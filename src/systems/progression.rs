@@ -0,0 +1,66 @@
+use bevy::prelude::*;
+
+// Modules
+use crate::{
+    events::{LevelUp, ScoreEvent},
+    resources::{GameState, GameTime, LevelThresholds},
+};
+
+/// Add scored points to the game state
+pub fn score_update(mut scores: EventReader<ScoreEvent>, mut game_state: ResMut<GameState>) {
+    for score in scores.read() {
+        game_state.add_score(score.points);
+    }
+}
+
+/// Advance the level once the score or game time passes the next threshold
+pub fn level_progression(
+    mut game_state: ResMut<GameState>,
+    game_time: Res<GameTime>,
+    thresholds: Res<LevelThresholds>,
+    mut level_ups: EventWriter<LevelUp>,
+) {
+    // Determine the level reached
+    let target: u32 = thresholds.level_for(game_state.score(), game_time.get());
+
+    // Step through each level so no level up is skipped
+    while game_state.level() < target {
+        let level: u32 = game_state.level_up();
+        info!("Level up: {level}");
+        let _event_id = level_ups.send(LevelUp { level });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_level_up_is_sent() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<GameState>()
+            .init_resource::<GameTime>()
+            .init_resource::<LevelThresholds>()
+            .add_event::<ScoreEvent>()
+            .add_event::<LevelUp>()
+            .add_systems(Update, (score_update, level_progression).chain());
+
+        // Score past two thresholds at once
+        let _event_id = app.world_mut().send_event(ScoreEvent { points: 250 });
+        app.update();
+
+        assert_eq!(app.world().resource::<GameState>().level(), 3);
+        let levels: Vec<u32> = app
+            .world_mut()
+            .resource_mut::<Events<LevelUp>>()
+            .drain()
+            .map(|level_up| level_up.level)
+            .collect();
+        assert_eq!(levels, vec![2, 3]);
+
+        // Nothing more until the next threshold
+        app.update();
+        assert!(app.world().resource::<Events<LevelUp>>().is_empty());
+    }
+}
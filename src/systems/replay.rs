@@ -0,0 +1,160 @@
+use bevy::prelude::*;
+use chrono::Utc;
+use std::path::PathBuf;
+
+// Modules
+use crate::{
+    config::config_path,
+    error::Error,
+    headless::{HeadlessRun, InputScript},
+    replay::{Replay, ReplayResult},
+    resources::{
+        Action, AnalogMovement, GameRng, GameState, GameTick, PauseState, ReplayPlayback,
+        ReplayRecording, ReplaySource,
+    },
+    systems::save::RunSnapshot,
+};
+
+/// Load the replay to play back, stopping the game if it can't be read
+pub fn replay_load(mut commands: Commands, source: Res<ReplaySource>) {
+    match Replay::load(&source.0) {
+        Ok(replay) => {
+            info!(
+                "Playing back {} ({} ticks)",
+                source.0.display(),
+                replay.ticks
+            );
+            commands.insert_resource(ReplayPlayback {
+                replay,
+                next_pause: 0,
+            });
+        }
+        // Errors logged automatically within Replay::load
+        Err(_) => {
+            commands.send_event(AppExit::error());
+        }
+    }
+}
+
+/// Start playing back the replay or recording the run, once the run is seeded
+pub fn replay_start(
+    mut commands: Commands,
+    playback: Option<ResMut<ReplayPlayback>>,
+    snapshot: Option<Res<RunSnapshot>>,
+    headless: Option<Res<HeadlessRun>>,
+    rng: Res<GameRng>,
+) {
+    // Play back the replay's input in place of the input devices
+    if let Some(mut playback) = playback {
+        commands.insert_resource(playback.replay.input.clone());
+        playback.next_pause = 0;
+        return;
+    }
+
+    // Record runs played from the start, as a continued run can't be replayed, and headless
+    // runs only when asked to
+    let record: bool = headless.is_none_or(|run| run.record.is_some());
+    if snapshot.is_none() && record {
+        commands.insert_resource(ReplayRecording(Replay::new(rng.seed())));
+    }
+}
+
+/// Record the input used by each fixed timestep
+pub fn replay_record(
+    mut recording: ResMut<ReplayRecording>,
+    actions: Res<ButtonInput<Action>>,
+    analog: Res<AnalogMovement>,
+    game_tick: Res<GameTick>,
+) {
+    // Pauses are recorded separately, as they don't take a fixed timestep
+    let held: Vec<Action> = actions
+        .get_pressed()
+        .copied()
+        .filter(|action| *action != Action::Pause)
+        .collect();
+
+    recording
+        .0
+        .input
+        .record(game_tick.get(), held, analog.get());
+}
+
+/// Record each pause, to replay it at the same fixed timestep
+pub fn replay_record_pause(mut recording: ResMut<ReplayRecording>, game_tick: Res<GameTick>) {
+    recording.0.pauses.push(game_tick.get());
+}
+
+/// Save the recorded run as it ends
+pub fn replay_save(
+    mut commands: Commands,
+    recording: Res<ReplayRecording>,
+    headless: Option<Res<HeadlessRun>>,
+    game_state: Res<GameState>,
+    game_tick: Res<GameTick>,
+) {
+    // Finish the replay
+    let mut replay: Replay = recording.0.clone();
+    replay.ticks = game_tick.get();
+    replay.result = Some(ReplayResult {
+        score: game_state.score(),
+        level: game_state.level(),
+    });
+
+    // Write the replay to the headless run's file, or to the replay directory named by when
+    // it was saved, deleting the oldest there so they don't pile up
+    let path: Result<PathBuf, Error> = match headless.and_then(|run| run.record.clone()) {
+        Some(path) => replay.save(&path).map(|()| path),
+        None => config_path(Replay::DIR_NAME).and_then(|dir| {
+            let path: PathBuf = dir.join(format!(
+                "{}.{}",
+                Utc::now().format("%Y-%m-%d_%H-%M-%S"),
+                Replay::EXTENSION
+            ));
+            replay.save(&path)?;
+            Replay::prune(&dir).ok();
+            Ok(path)
+        }),
+    };
+    path.map(|path| info!("Saved the replay to {}", path.display()))
+        // Errors logged automatically within config_path, Replay::save and Replay::prune
+        .ok();
+
+    commands.remove_resource::<ReplayRecording>();
+}
+
+/// Pause and resume at each recorded pause, through the pause toggle
+pub fn replay_pause(
+    mut playback: ResMut<ReplayPlayback>,
+    pause: Res<State<PauseState>>,
+    game_tick: Res<GameTick>,
+    mut actions: ResMut<ButtonInput<Action>>,
+) {
+    let due: bool = playback
+        .replay
+        .pauses
+        .get(playback.next_pause)
+        .is_some_and(|tick| *tick <= game_tick.get());
+    if !due {
+        return;
+    }
+
+    // Resume straight away, as a pause doesn't change the run
+    if *pause.get() == PauseState::Paused {
+        playback.next_pause += 1;
+    }
+
+    // Press pause afresh
+    actions.release(Action::Pause);
+    actions.press(Action::Pause);
+}
+
+/// Check whether the replay has played out
+pub fn replay_finished(playback: Option<Res<ReplayPlayback>>, game_tick: Res<GameTick>) -> bool {
+    playback.is_some_and(|playback| game_tick.get() >= playback.replay.ticks)
+}
+
+/// Stop playing back the replay, handing control back to the input devices
+pub fn replay_stop(mut commands: Commands) {
+    commands.remove_resource::<ReplayPlayback>();
+    commands.remove_resource::<InputScript>();
+}
@@ -0,0 +1,22 @@
+use bevy::prelude::*;
+
+// Modules
+use crate::resources::{GameRng, GameSettings, ReplayPlayback, SeedOverride};
+
+/// Seed the run's random numbers from the replay, the command line or the settings, or at
+/// random, before a continued run restores its own
+pub fn rng_seed(
+    mut commands: Commands,
+    playback: Option<Res<ReplayPlayback>>,
+    seed_override: Option<Res<SeedOverride>>,
+    settings: Res<GameSettings>,
+) {
+    let seed: u64 = match (playback, seed_override, settings.seed()) {
+        (Some(playback), _, _) => playback.replay.seed,
+        (None, Some(seed_override), _) => seed_override.0,
+        (None, None, Some(seed)) => seed,
+        (None, None, None) => rand::random::<u64>(),
+    };
+
+    commands.insert_resource(GameRng::new(seed));
+}
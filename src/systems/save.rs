@@ -0,0 +1,496 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+// Modules
+use crate::{
+    components::{
+        button::{DisabledButton, StartMenuButton},
+        level::LevelEntity,
+        movement::{Interpolated, Velocity},
+        obstacle::{Hazard, HazardSpawner, Lifetime, Patrol, Wall},
+        pickup::{Pickup, PowerUps},
+        player::{Health, Invulnerable, Lives, Player, SpawnPoint},
+    },
+    config::{config_path, load_ron, save_ron},
+    error::{Error, ErrorLevel},
+    level::{LevelDefinition, PickupDefinition},
+    resources::{AppState, CurrentLevel, GameRng, GameState, GameTime, RngState, SavePath},
+    systems::{
+        obstacle::{spawn_spawner, HazardAssets},
+        pickup::PickupSchedule,
+    },
+};
+
+/// Save file name, in the platform config directory
+const SAVE_FILE_NAME: &str = "savegame.ron";
+
+/// Save file schema version
+const SAVE_VERSION: u32 = 1;
+
+/// Save file contents, tagged with the schema version
+#[derive(Serialize, Deserialize)]
+struct SaveFile {
+    version: u32,
+    run: RunSnapshot,
+}
+
+/// Snapshot of a run in progress, held as a resource while it's being restored
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+pub struct RunSnapshot {
+    level: u32,
+    level_definition: LevelDefinition,
+    score: u32,
+    game_time: f32,
+    player: PlayerSnapshot,
+    patrols: Vec<PatrolSnapshot>,
+    spawners: Vec<SpawnerSnapshot>,
+    hazards: Vec<HazardSnapshot>,
+    pickups: Vec<PickupDefinition>,
+    pickup_next_spawn: f32,
+    pickup_index: usize,
+    #[serde(default)]
+    rng: RngState,
+}
+
+impl RunSnapshot {
+    /// Get the run's level as it was saved, rather than reloading its file
+    pub fn current_level(&self) -> CurrentLevel {
+        CurrentLevel {
+            number: self.level,
+            definition: self.level_definition.clone(),
+            handle: Handle::default(),
+        }
+    }
+}
+
+/// Player state in a snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PlayerSnapshot {
+    position: Vec2,
+    velocity: Vec2,
+    spawn_point: Vec2,
+    health: u32,
+    lives: u32,
+    invulnerable_until: f32,
+    power_ups: PowerUps,
+}
+
+/// Patrolling hazard in a snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PatrolSnapshot {
+    position: Vec2,
+    patrol: Patrol,
+}
+
+/// Hazard spawner in a snapshot
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct SpawnerSnapshot {
+    position: Vec2,
+    spawner: HazardSpawner,
+}
+
+/// Spawned hazard in a snapshot
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct HazardSnapshot {
+    position: Vec2,
+    velocity: Vec2,
+    lifetime: Lifetime,
+}
+
+/// Progress of the run, for a snapshot
+#[derive(SystemParam)]
+pub struct RunProgress<'w> {
+    game_state: Res<'w, GameState>,
+    game_time: Res<'w, GameTime>,
+    rng: Res<'w, GameRng>,
+    level: Res<'w, CurrentLevel>,
+}
+
+/// Hazards and pickups in play, for a snapshot
+#[derive(SystemParam)]
+pub struct LevelEntities<'w, 's> {
+    schedule: Option<Res<'w, PickupSchedule>>,
+    patrols: Query<'w, 's, (&'static Transform, &'static Patrol)>,
+    spawners: Query<'w, 's, (&'static Transform, &'static HazardSpawner)>,
+    hazards:
+        Query<'w, 's, (&'static Transform, &'static Velocity, &'static Lifetime), With<Hazard>>,
+    pickups: Query<'w, 's, (&'static Transform, &'static Pickup)>,
+}
+
+/// Find the save file in the platform config directory
+pub fn save_path_init(mut commands: Commands) {
+    // Errors logged automatically within config_path
+    if let Ok(path) = config_path(SAVE_FILE_NAME) {
+        commands.insert_resource(SavePath(path));
+    }
+}
+
+/// Save the run in progress to the save file
+pub fn run_save(
+    save_path: Option<Res<SavePath>>,
+    progress: RunProgress,
+    players: Query<(&Transform, &Velocity, &SpawnPoint), With<Player>>,
+    vitals: Query<(&Health, &Lives, &Invulnerable, &PowerUps), With<Player>>,
+    entities: LevelEntities,
+) {
+    let Some(save_path) = save_path else {
+        Error::new(
+            ErrorLevel::Warning,
+            "Unable to save the run without a save file",
+            None,
+        );
+        return;
+    };
+
+    // Get the player
+    let (Ok((transform, velocity, spawn_point)), Ok((health, lives, invulnerable, power_ups))) =
+        (players.get_single(), vitals.get_single())
+    else {
+        Error::new(
+            ErrorLevel::Warning,
+            "Unable to save the run without a player",
+            None,
+        );
+        return;
+    };
+
+    // Take the snapshot
+    let run = RunSnapshot {
+        level: progress.level.number,
+        level_definition: progress.level.definition.clone(),
+        score: progress.game_state.score(),
+        game_time: progress.game_time.get(),
+        player: PlayerSnapshot {
+            position: transform.translation.truncate(),
+            velocity: velocity.get(),
+            spawn_point: spawn_point.0,
+            health: health.current(),
+            lives: lives.get(),
+            invulnerable_until: invulnerable.until,
+            power_ups: power_ups.clone(),
+        },
+        patrols: entities
+            .patrols
+            .iter()
+            .map(|(transform, patrol)| PatrolSnapshot {
+                position: transform.translation.truncate(),
+                patrol: patrol.clone(),
+            })
+            .collect(),
+        spawners: entities
+            .spawners
+            .iter()
+            .map(|(transform, spawner)| SpawnerSnapshot {
+                position: transform.translation.truncate(),
+                spawner: *spawner,
+            })
+            .collect(),
+        hazards: entities
+            .hazards
+            .iter()
+            .map(|(transform, velocity, lifetime)| HazardSnapshot {
+                position: transform.translation.truncate(),
+                velocity: velocity.get(),
+                lifetime: *lifetime,
+            })
+            .collect(),
+        pickups: entities
+            .pickups
+            .iter()
+            .map(|(transform, pickup)| PickupDefinition {
+                kind: *pickup,
+                position: transform.translation.truncate(),
+            })
+            .collect(),
+        pickup_next_spawn: entities
+            .schedule
+            .as_ref()
+            .map_or(0.0, |schedule| schedule.next_spawn),
+        pickup_index: entities
+            .schedule
+            .as_ref()
+            .map_or(0, |schedule| schedule.index),
+        rng: progress.rng.state(),
+    };
+
+    // Write the save file
+    let file = SaveFile {
+        version: SAVE_VERSION,
+        run,
+    };
+    // Errors logged automatically within save_ron
+    if save_ron(&save_path.0, &file).is_ok() {
+        info!("Saved the run at {:.1}s", file.run.game_time);
+    }
+}
+
+/// Load the saved run, if there is a readable one
+fn run_load(path: &Path) -> Option<RunSnapshot> {
+    load_ron::<SaveFile>(path)
+        .and_then(|file| {
+            file.map(|file| match file.version {
+                SAVE_VERSION => Ok(file.run),
+                version => Err(Error::new(
+                    ErrorLevel::Warning,
+                    &format!("Save file version {version} isn't supported, ignoring it"),
+                    None,
+                )),
+            })
+            .transpose()
+        })
+        // Errors logged automatically within load_ron and the version check
+        .ok()
+        .flatten()
+}
+
+/// Continue the saved run on its saved level, once the level files have loaded
+pub fn run_continue(mut commands: Commands, save_path: Option<Res<SavePath>>) {
+    let Some(snapshot) = save_path.and_then(|path| run_load(&path.0)) else {
+        return;
+    };
+
+    // Restore the run once the level files have loaded and its level has spawned
+    commands.insert_resource(snapshot);
+    commands.set_state(AppState::Loading);
+}
+
+/// Disable the continue button when there is no saved run that can be loaded
+pub fn continue_available(
+    mut commands: Commands,
+    save_path: Option<Res<SavePath>>,
+    buttons: Query<(Entity, &StartMenuButton), Added<StartMenuButton>>,
+) {
+    for (entity, button) in &buttons {
+        if *button == StartMenuButton::Continue
+            && save_path
+                .as_ref()
+                .is_none_or(|path| run_load(&path.0).is_none())
+        {
+            commands.entity(entity).insert(DisabledButton);
+        }
+    }
+}
+
+/// Restore the run's progress, random numbers and the player from the snapshot
+pub fn run_restore(
+    snapshot: Res<RunSnapshot>,
+    mut game_state: ResMut<GameState>,
+    mut game_time: ResMut<GameTime>,
+    mut rng: ResMut<GameRng>,
+    mut bodies: Query<
+        (
+            &mut Transform,
+            &mut Velocity,
+            &mut SpawnPoint,
+            &mut Interpolated,
+        ),
+        With<Player>,
+    >,
+    mut vitals: Query<(&mut Health, &mut Lives, &mut Invulnerable, &mut PowerUps), With<Player>>,
+) {
+    // Restore the progress
+    *game_state = GameState::new(snapshot.score, snapshot.level);
+    game_time.0 = snapshot.game_time;
+    *rng = GameRng::restore(&snapshot.rng);
+
+    // Restore the player
+    let player: &PlayerSnapshot = &snapshot.player;
+    for (mut transform, mut velocity, mut spawn_point, mut interpolated) in &mut bodies {
+        transform.translation = player.position.extend(transform.translation.z);
+        velocity.set(player.velocity);
+        spawn_point.0 = player.spawn_point;
+        interpolated.teleport(transform.translation);
+    }
+    for (mut health, mut lives, mut invulnerable, mut power_ups) in &mut vitals {
+        health.set_current(player.health);
+        lives.0 = player.lives;
+        invulnerable.until = player.invulnerable_until;
+        *power_ups = player.power_ups.clone();
+    }
+}
+
+/// Replace the level's hazards and pickups with those in the snapshot, finishing the restore
+pub fn world_restore(
+    mut commands: Commands,
+    snapshot: Res<RunSnapshot>,
+    entities: Query<Entity, (With<LevelEntity>, Without<Wall>)>,
+    hazard_assets: Res<HazardAssets>,
+    mut schedule: ResMut<PickupSchedule>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    // Clear the freshly spawned hazards and pickups, keeping the walls
+    for entity in &entities {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    // Restore the hazards
+    for patrol in &snapshot.patrols {
+        hazard_assets.spawn_patrol(&mut commands, patrol.position, patrol.patrol.clone());
+    }
+    for spawner in &snapshot.spawners {
+        spawn_spawner(&mut commands, spawner.position, spawner.spawner);
+    }
+    for hazard in &snapshot.hazards {
+        hazard_assets.spawn_hazard(
+            &mut commands,
+            hazard.position,
+            hazard.velocity,
+            hazard.lifetime,
+        );
+    }
+
+    // Restore the pickups
+    for pickup in &snapshot.pickups {
+        schedule.spawn(&mut commands, &mut materials, *pickup);
+    }
+    schedule.next_spawn = snapshot.pickup_next_spawn;
+    schedule.index = snapshot.pickup_index;
+
+    commands.remove_resource::<RunSnapshot>();
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+    use std::{fs, path::PathBuf};
+
+    // Modules
+    use super::*;
+    use crate::{
+        headless::{HeadlessRun, InputScript, ScriptStep},
+        resources::Action,
+        systems::time::FIXED_TIMESTEP_HZ,
+        GamePlugin,
+    };
+
+    /// Get a save file path in a scratch directory of its own
+    fn scratch_path(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("dorian-{name}-{}", std::process::id()))
+            .join(SAVE_FILE_NAME)
+    }
+
+    /// Build a headless app saving to a path
+    fn headless_app(path: &Path) -> App {
+        let mut app = App::new();
+        app.add_plugins(GamePlugin::headless(HeadlessRun::ticks(u64::MAX)).seed(7))
+            .insert_resource(SavePath(path.to_path_buf()));
+        app
+    }
+
+    /// Update until the run has started and any saved run has been restored
+    fn start(app: &mut App) {
+        for _ in 0..1000 {
+            app.update();
+            if *app.world().resource::<State<AppState>>().get() == AppState::InGame
+                && !app.world().contains_resource::<RunSnapshot>()
+            {
+                return;
+            }
+        }
+        panic!("the run didn't start");
+    }
+
+    /// Get the score, level, player position and the number of hazards and pickups in play
+    fn progress(app: &mut App) -> (u32, u32, Vec2, usize, usize) {
+        let game_state: &GameState = app.world().resource::<GameState>();
+        let (score, level): (u32, u32) = (game_state.score(), game_state.level());
+        let world: &mut World = app.world_mut();
+        let position: Vec2 = world
+            .query_filtered::<&Transform, With<Player>>()
+            .get_single(world)
+            .map_or(Vec2::NAN, |transform| transform.translation.truncate());
+        let hazards: usize = world
+            .query_filtered::<(), With<Hazard>>()
+            .iter(world)
+            .count();
+        let pickups: usize = world.query::<&Pickup>().iter(world).count();
+        (score, level, position, hazards, pickups)
+    }
+
+    #[test]
+    fn saved_runs_continue_where_they_left_off() {
+        let path: PathBuf = scratch_path("save");
+
+        // Move away from the spawn point, and play on until hazards have spawned
+        let mut app = headless_app(&path);
+        app.insert_resource(InputScript {
+            steps: vec![
+                ScriptStep {
+                    tick: 0,
+                    held: vec![Action::MoveDown],
+                    stick: Vec2::ZERO,
+                },
+                ScriptStep {
+                    tick: 32,
+                    held: Vec::new(),
+                    stick: Vec2::ZERO,
+                },
+            ],
+        });
+        start(&mut app);
+        for _ in 0..FIXED_TIMESTEP_HZ as u32 * 6 {
+            app.update();
+        }
+        // Draw from a stream, so the save holds how far it has been drawn
+        let _drawn: u32 = app
+            .world_mut()
+            .resource_mut::<GameRng>()
+            .stream(GameRng::SPAWNING)
+            .gen();
+        assert!(app.world_mut().run_system_cached(run_save).is_ok());
+        let saved_time: f32 = app.world().resource::<GameTime>().get();
+        let saved_rng: RngState = app.world().resource::<GameRng>().state();
+        let saved: (u32, u32, Vec2, usize, usize) = progress(&mut app);
+        assert!(saved.3 > 0, "no hazards to save");
+
+        // Continue the run in a new app, once it has started loading as from the menu
+        let mut app = headless_app(&path);
+        app.update();
+        assert!(app.world_mut().run_system_cached(run_continue).is_ok());
+        start(&mut app);
+        let (score, level, position, hazards, pickups) = progress(&mut app);
+        let game_time: f32 = app.world().resource::<GameTime>().get();
+        fs::remove_dir_all(path.parent().unwrap_or(&path)).ok();
+
+        // Carried on from the save, a timestep later
+        assert_eq!(
+            (score, level, hazards, pickups),
+            (saved.0, saved.1, saved.3, saved.4)
+        );
+        assert!(
+            position.distance(saved.2) < 0.1,
+            "{position} vs {}",
+            saved.2
+        );
+        assert_eq!(game_time, saved_time + 1.0 / FIXED_TIMESTEP_HZ as f32);
+        assert_eq!(app.world().resource::<GameRng>().state(), saved_rng);
+    }
+
+    #[test]
+    fn continue_needs_a_loadable_save() {
+        let path: PathBuf = scratch_path("continue");
+        let continue_disabled = |path: &Path| {
+            let mut app = App::new();
+            app.insert_resource(SavePath(path.to_path_buf()))
+                .add_systems(Update, continue_available);
+            let button: Entity = app.world_mut().spawn(StartMenuButton::Continue).id();
+            app.update();
+            app.world().get::<DisabledButton>(button).is_some()
+        };
+
+        // Missing, corrupt and from another version
+        assert!(continue_disabled(&path));
+        assert!(fs::create_dir_all(path.parent().unwrap_or(&path)).is_ok());
+        let files = [
+            "not a save".to_string(),
+            format!("(version: {}, run: ())", SAVE_VERSION + 1),
+        ];
+        for file in files {
+            assert!(fs::write(&path, file).is_ok());
+            assert!(continue_disabled(&path));
+        }
+        fs::remove_dir_all(path.parent().unwrap_or(&path)).ok();
+    }
+}
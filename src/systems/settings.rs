@@ -0,0 +1,319 @@
+use bevy::{
+    prelude::*,
+    window::{MonitorSelection, PresentMode, PrimaryWindow, WindowMode},
+};
+use ron::Value;
+use serde::Serialize;
+
+// Modules
+use crate::{
+    components::{
+        button::{BindingsMenuButton, ButtonValues, SettingsMenuButton},
+        menu::{MenuSelection, Rebinding},
+    },
+    config::{config_path, load_ron, save_ron},
+    error::{Error, ErrorLevel},
+    resources::{Action, GameSettings, InputBindings},
+};
+
+/// Settings file contents, tagged with the schema version
+#[derive(Serialize)]
+struct SettingsFile<'a> {
+    version: u32,
+    settings: &'a GameSettings,
+}
+
+/// Load the settings from the config file, falling back to the defaults
+pub fn settings_load() -> GameSettings {
+    config_path(GameSettings::FILE_NAME)
+        .and_then(|path| load_ron::<Value>(&path))
+        .and_then(|file| file.map(settings_migrate).transpose())
+        // Errors logged automatically within the config helpers and migration
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+}
+
+/// Upgrade a settings file to the current schema version
+fn settings_migrate(file: Value) -> Result<GameSettings, Error> {
+    // Files without a version predate the schema, and hold the settings directly
+    let (version, settings): (u32, Value) = match file {
+        Value::Map(mut map) => match map.remove(&Value::String("version".to_string())) {
+            Some(version) => (
+                version
+                    .into_rust()
+                    .map_err(|err| settings_error("Invalid settings file version", err))?,
+                map.remove(&Value::String("settings".to_string()))
+                    .unwrap_or(Value::Map(map)),
+            ),
+            None => (0, Value::Map(map)),
+        },
+        file => (0, file),
+    };
+
+    // Refuse files written by a newer version of the game
+    if version > GameSettings::VERSION {
+        return Err(Error::new(
+            ErrorLevel::Warning,
+            &format!(
+                "Settings file version {version} is newer than {}, using defaults",
+                GameSettings::VERSION
+            ),
+            None,
+        ));
+    }
+
+    // Read the settings, with missing fields taking their defaults. Version 0 only
+    // lacked the version tag, which is unwrapped above, so needs no further migration
+    settings
+        .into_rust()
+        .map_err(|err| settings_error("Unable to read settings file, using defaults", err))
+}
+
+/// Build a warning for an unreadable settings file
+fn settings_error(message: &str, err: ron::Error) -> Error {
+    Error::new(ErrorLevel::Warning, message, Some(Box::new(err)))
+}
+
+/// Save the settings to the config file
+pub fn settings_save(settings: Res<GameSettings>) {
+    let file = SettingsFile {
+        version: GameSettings::VERSION,
+        settings: &settings,
+    };
+
+    config_path(GameSettings::FILE_NAME)
+        .and_then(|path| save_ron(&path, &file))
+        // Errors logged automatically within the config helpers
+        .ok();
+}
+
+/// Adjust the selected setting with the left and right actions
+pub fn settings_adjust(
+    actions: Res<ButtonInput<Action>>,
+    selection: Res<MenuSelection>,
+    mut settings: ResMut<GameSettings>,
+) {
+    // Determine the adjustment direction
+    let forward: bool = if actions.just_pressed(Action::MoveRight) {
+        true
+    } else if actions.just_pressed(Action::MoveLeft) {
+        false
+    } else {
+        return;
+    };
+
+    // Adjust the selected setting
+    SettingsMenuButton::from_index(selection.index())
+        .map(|button| button.adjust(&mut settings, forward, false))
+        // Errors logged automatically within from_index
+        .ok();
+}
+
+/// Apply the window settings whenever they change, only touching the ones that changed
+pub fn settings_apply(
+    settings: Res<GameSettings>,
+    mut applied: Local<Option<GameSettings>>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    // Skip when running without a window
+    let Ok(mut window) = windows.get_single_mut() else {
+        return;
+    };
+
+    // Set the window mode
+    if setting_changed(applied.as_ref(), &settings, GameSettings::fullscreen) {
+        window.mode = if settings.fullscreen() {
+            WindowMode::BorderlessFullscreen(MonitorSelection::Current)
+        } else {
+            WindowMode::Windowed
+        };
+    }
+
+    // Set the present mode
+    if setting_changed(applied.as_ref(), &settings, GameSettings::vsync) {
+        window.present_mode = if settings.vsync() {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        };
+    }
+
+    // Set the resolution
+    if setting_changed(applied.as_ref(), &settings, GameSettings::resolution) {
+        let resolution: UVec2 = settings.resolution();
+        window
+            .resolution
+            .set(resolution.x as f32, resolution.y as f32);
+    }
+
+    *applied = Some(settings.clone());
+}
+
+/// Check whether a setting differs from the one last applied, or was never applied
+fn setting_changed<T: PartialEq>(
+    applied: Option<&GameSettings>,
+    settings: &GameSettings,
+    get: fn(&GameSettings) -> T,
+) -> bool {
+    applied.is_none_or(|applied| get(applied) != get(settings))
+}
+
+/// Show the current value of each setting on its button
+pub fn settings_labels(
+    settings: Res<GameSettings>,
+    buttons: Query<(&SettingsMenuButton, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    for (button, children) in &buttons {
+        set_label(&mut texts, children, button.label(&settings));
+    }
+}
+
+/// Show the keys bound to each action on its button
+pub fn bindings_labels(
+    bindings: Res<InputBindings>,
+    rebinding: Res<Rebinding>,
+    buttons: Query<(&BindingsMenuButton, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    for (button, children) in &buttons {
+        set_label(
+            &mut texts,
+            children,
+            button.label(&bindings, rebinding.get()),
+        );
+    }
+}
+
+/// Set the text of a button, only touching it when the label changes
+fn set_label(texts: &mut Query<&mut Text>, children: &Children, label: String) {
+    let mut iter = texts.iter_many_mut(children);
+    while let Some(mut text) = iter.fetch_next() {
+        if text.0 != label {
+            text.0.clone_from(&label);
+        }
+    }
+}
+
+/// Bind the next key to the action waiting to be rebound
+pub fn bindings_capture(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut rebinding: ResMut<Rebinding>,
+    mut bindings: ResMut<InputBindings>,
+) {
+    if let Some((action, key)) = rebinding.capture(&keyboard_input) {
+        bindings.rebind(action, key);
+    }
+}
+
+/// Check that no action is waiting to be rebound
+pub fn rebinding_inactive(rebinding: Res<Rebinding>) -> bool {
+    rebinding.get().is_none()
+}
+
+/// Stop waiting for a key when leaving the key bindings menu
+pub fn rebinding_reset(mut rebinding: ResMut<Rebinding>) {
+    *rebinding = Rebinding::default();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parse a settings file and upgrade it to the current schema version
+    fn migrate(file: &str) -> Result<GameSettings, Error> {
+        let file: Value = ron::from_str(file).map_err(|err| {
+            Error::new(ErrorLevel::Error, "Invalid test file", Some(Box::new(err)))
+        })?;
+        settings_migrate(file)
+    }
+
+    #[test]
+    fn untagged_files_hold_the_settings() {
+        let settings: Option<GameSettings> = migrate("(master_volume: 0.5, vsync: false)").ok();
+        let mut expected = GameSettings::default();
+        expected.set_master_volume(0.5);
+        expected.set_vsync(false);
+        assert_eq!(settings, Some(expected));
+    }
+
+    #[test]
+    fn saved_files_load_back() {
+        let mut settings = GameSettings::default();
+        settings.set_sfx_volume(0.25);
+        let file = SettingsFile {
+            version: GameSettings::VERSION,
+            settings: &settings,
+        };
+
+        let saved: String = ron::to_string(&file).unwrap_or_default();
+        assert_eq!(migrate(&saved).ok(), Some(settings));
+    }
+
+    #[test]
+    fn newer_files_are_refused() {
+        let file: String = format!("(version: {}, settings: ())", GameSettings::VERSION + 1);
+        assert!(migrate(&file).is_err());
+    }
+
+    #[test]
+    fn corrupt_files_are_refused() {
+        assert!(migrate("(version: \"one\", settings: ())").is_err());
+        assert!(migrate("(version: 1, settings: (fullscreen: \"yes\"))").is_err());
+        assert!(migrate("\"loud\"").is_err());
+    }
+
+    #[test]
+    fn only_changed_window_settings_are_applied() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<GameSettings>()
+            .add_systems(
+                Update,
+                settings_apply.run_if(resource_changed::<GameSettings>),
+            );
+        let window: Entity = app
+            .world_mut()
+            .spawn((Window::default(), PrimaryWindow))
+            .id();
+        app.update();
+
+        // The window is resized by hand
+        let resolution: UVec2 = GameSettings::default().resolution();
+        let get_window = |app: &App| app.world().get::<Window>(window).cloned();
+        assert_eq!(
+            get_window(&app).map(|window| window.resolution.size()),
+            Some(resolution.as_vec2())
+        );
+        if let Some(mut window) = app.world_mut().get_mut::<Window>(window) {
+            window.resolution.set(640.0, 360.0);
+        }
+
+        // Changing the volume leaves the window alone
+        app.world_mut()
+            .resource_mut::<GameSettings>()
+            .set_master_volume(0.5);
+        app.update();
+        assert_eq!(
+            get_window(&app).map(|window| window.resolution.size()),
+            Some(Vec2::new(640.0, 360.0))
+        );
+
+        // Changing the window mode keeps the resolution
+        let fullscreen: bool = GameSettings::default().fullscreen();
+        app.world_mut()
+            .resource_mut::<GameSettings>()
+            .set_fullscreen(!fullscreen);
+        app.update();
+        let window: Option<Window> = get_window(&app);
+        assert_eq!(
+            window.as_ref().map(|window| window.resolution.size()),
+            Some(Vec2::new(640.0, 360.0))
+        );
+        assert_eq!(
+            window.map(|window| window.mode == WindowMode::Windowed),
+            Some(fullscreen)
+        );
+    }
+}
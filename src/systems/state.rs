@@ -0,0 +1,20 @@
+use bevy::prelude::*;
+
+// Modules
+use crate::resources::{GameState, GameTick, GameTime, PauseState};
+
+/// Reset the run resources so each run starts clean
+pub fn game_reset(
+    mut game_state: ResMut<GameState>,
+    mut game_time: ResMut<GameTime>,
+    mut game_tick: ResMut<GameTick>,
+) {
+    *game_state = GameState::default();
+    *game_time = GameTime::default();
+    *game_tick = GameTick::default();
+}
+
+/// Unpause when leaving a run, which also closes the in-game menu
+pub fn pause_reset(mut next_pause: ResMut<NextState<PauseState>>) {
+    next_pause.set(PauseState::Running);
+}
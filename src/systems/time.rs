@@ -1,9 +1,98 @@
 use bevy::prelude::*;
 
 // Modules
-use crate::resources::GameTime;
+use crate::resources::{GameTick, GameTime};
 
-/// Tick the game time
-pub fn tick_game_time(mut game_time: ResMut<GameTime>, time: Res<Time>) {
+/// Simulation rate, in fixed timesteps per second
+pub const FIXED_TIMESTEP_HZ: f64 = 64.0;
+
+/// Tick the game time, once per fixed timestep
+pub fn tick_game_time(
+    mut game_time: ResMut<GameTime>,
+    mut game_tick: ResMut<GameTick>,
+    time: Res<Time>,
+) {
     game_time.0 += time.delta_secs();
+    game_tick.0 += 1;
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    // Modules
+    use super::*;
+    use crate::{
+        components::player::Player,
+        headless::{HeadlessRun, InputScript, ScriptStep},
+        resources::{Action, AppState, CurrentLevel},
+        systems::movement::interpolation_restore,
+        tuning::Tuning,
+        GamePlugin,
+    };
+
+    #[test]
+    fn each_update_steps_one_fixed_timestep() {
+        let mut app = App::new();
+        app.add_plugins(GamePlugin::headless(HeadlessRun::ticks(u64::MAX)))
+            .insert_resource(InputScript {
+                steps: vec![ScriptStep {
+                    tick: 0,
+                    held: vec![Action::MoveRight],
+                    stick: Vec2::ZERO,
+                }],
+            });
+
+        // Load the level, stepping the first timestep as the run starts
+        for _ in 0..1000 {
+            if *app.world().resource::<State<AppState>>().get() == AppState::InGame {
+                break;
+            }
+            app.update();
+        }
+        assert_eq!(app.world().resource::<GameTick>().get(), 1);
+
+        const TICKS: u64 = 16;
+        for _ in 1..TICKS {
+            app.update();
+        }
+        assert_eq!(app.world().resource::<GameTick>().get(), TICKS);
+        assert_eq!(
+            app.world().resource::<GameTime>().get(),
+            TICKS as f32 / FIXED_TIMESTEP_HZ as f32
+        );
+
+        // Step the player's movement by hand, accelerating right from the spawn point
+        let player = app.world().resource::<Tuning>().player;
+        let delta: f32 = 1.0 / FIXED_TIMESTEP_HZ as f32;
+        let mut velocity: Vec2 = Vec2::ZERO;
+        let mut positions: Vec<Vec3> = vec![app
+            .world()
+            .resource::<CurrentLevel>()
+            .definition
+            .spawn_point
+            .extend(0.0)];
+        for _ in 0..TICKS {
+            velocity += Vec2::X * player.acceleration * delta;
+            velocity /= 1.0 + player.drag * delta;
+            velocity = velocity.clamp_length_max(player.max_speed);
+            positions.push(positions[positions.len() - 1] + velocity.extend(0.0) * delta);
+        }
+
+        // Rendered a whole timestep behind, with no time left over between updates
+        let position = |app: &mut App| {
+            app.world_mut()
+                .query_filtered::<&Transform, With<Player>>()
+                .single(app.world())
+                .translation
+        };
+        assert_eq!(position(&mut app), positions[TICKS as usize - 1]);
+
+        // Simulated up to the latest timestep
+        assert!(app
+            .world_mut()
+            .run_system_once(interpolation_restore)
+            .is_ok());
+        assert_eq!(position(&mut app), positions[TICKS as usize]);
+    }
 }
@@ -0,0 +1,29 @@
+use bevy::prelude::*;
+
+// Modules
+use crate::{resources::TuningHandle, tuning::Tuning};
+
+/// Start loading the tuning file, keeping the defaults until it loads
+pub fn tuning_load(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(TuningHandle(asset_server.load(Tuning::PATH)));
+}
+
+/// Apply the tuning file when it loads, and whenever it changes on disk in dev builds
+pub fn tuning_reload(
+    mut events: EventReader<AssetEvent<Tuning>>,
+    handle: Res<TuningHandle>,
+    assets: Res<Assets<Tuning>>,
+    mut tuning: ResMut<Tuning>,
+) {
+    for event in events.read() {
+        if !(event.is_loaded_with_dependencies(&handle.0) || event.is_modified(&handle.0)) {
+            continue;
+        }
+        if let Some(loaded) = assets.get(&handle.0) {
+            if *tuning != *loaded {
+                info!("Applied tuning from {}", Tuning::PATH);
+                *tuning = loaded.clone();
+            }
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext},
+    prelude::*,
+};
+use serde::Deserialize;
+
+// Modules
+use crate::{
+    config::read_ron_asset,
+    error::{Error, ErrorLevel},
+};
+
+/// Gameplay tuning, loaded from a `.tuning.ron` asset and hot-reloaded in dev builds
+#[derive(Asset, TypePath, Resource, Debug, Clone, PartialEq, Deserialize, Default)]
+#[serde(default)]
+pub struct Tuning {
+    pub player: PlayerTuning,
+    pub lighting: LightingTuning,
+}
+
+/// Player size, movement and colour
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct PlayerTuning {
+    /// Width and height, in world units
+    pub size: f32,
+    /// Top speed, in world units per second
+    pub max_speed: f32,
+    /// Acceleration applied at full input, in world units per second squared
+    pub acceleration: f32,
+    /// Deceleration applied while coasting, in world units per second squared
+    pub friction: f32,
+    /// Velocity lost per second in proportion to speed
+    pub drag: f32,
+    /// Base colour, as sRGB components from 0 to 1
+    pub colour: (f32, f32, f32),
+}
+
+/// Light intensity and shadows, on top of each level's lighting
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct LightingTuning {
+    /// Scales the level's light brightness
+    pub intensity: f32,
+    pub shadows_enabled: bool,
+    pub shadow_depth_bias: f32,
+    pub shadow_normal_bias: f32,
+}
+
+impl Default for PlayerTuning {
+    fn default() -> Self {
+        Self {
+            size: 1.0,
+            max_speed: 5.0,
+            acceleration: 30.0,
+            friction: 20.0,
+            drag: 0.5,
+            colour: (0.25, 0.75, 0.25),
+        }
+    }
+}
+
+impl Default for LightingTuning {
+    fn default() -> Self {
+        Self {
+            intensity: 1.0,
+            shadows_enabled: true,
+            shadow_depth_bias: 0.02,
+            shadow_normal_bias: 1.8,
+        }
+    }
+}
+
+impl PlayerTuning {
+    /// Get the base colour
+    pub fn colour(&self) -> Color {
+        let (red, green, blue) = self.colour;
+        Color::srgb(red, green, blue)
+    }
+}
+
+impl Tuning {
+    /// Asset path of the tuning file
+    pub const PATH: &'static str = "game.tuning.ron";
+
+    /// File extension for tuning assets
+    pub const EXTENSION: &'static str = "tuning.ron";
+
+    /// Check the values are usable, describing the first problem found
+    pub fn validate(&self) -> Result<(), String> {
+        let player: &PlayerTuning = &self.player;
+        for (name, value) in [
+            ("player size", player.size),
+            ("player max speed", player.max_speed),
+            ("player acceleration", player.acceleration),
+        ] {
+            if !(value.is_finite() && value > 0.0) {
+                return Err(format!("the {name} must be above zero"));
+            }
+        }
+
+        let lighting: &LightingTuning = &self.lighting;
+        let (red, green, blue) = player.colour;
+        for (name, value) in [
+            ("player friction", player.friction),
+            ("player drag", player.drag),
+            ("player colour red", red),
+            ("player colour green", green),
+            ("player colour blue", blue),
+            ("light intensity", lighting.intensity),
+            ("shadow depth bias", lighting.shadow_depth_bias),
+            ("shadow normal bias", lighting.shadow_normal_bias),
+        ] {
+            if !(value.is_finite() && value >= 0.0) {
+                return Err(format!("the {name} can't be negative"));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Loads and validates `.tuning.ron` assets
+#[derive(Default)]
+pub struct TuningLoader;
+
+impl AssetLoader for TuningLoader {
+    type Asset = Tuning;
+    type Settings = ();
+    type Error = Error;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Tuning, Error> {
+        // Read the tuning
+        let tuning: Tuning = read_ron_asset(reader, load_context.path(), "tuning").await?;
+
+        // Validate the tuning
+        tuning.validate().map_err(|problem| {
+            Error::new(
+                ErrorLevel::Error,
+                &format!(
+                    "Invalid tuning file {}: {problem}",
+                    load_context.path().display()
+                ),
+                None,
+            )
+        })?;
+
+        Ok(tuning)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &[Tuning::EXTENSION]
+    }
+}
@@ -0,0 +1,30 @@
+#![allow(unused_crate_dependencies)]
+use bevy::prelude::*;
+use dorian::{GamePlugin, HeadlessRun, Replay};
+use std::{env, fs, path::Path, process};
+
+#[test]
+fn recorded_run_plays_back_the_same() {
+    let path = env::temp_dir().join(format!("dorian-{}.{}", process::id(), Replay::EXTENSION));
+
+    // Record a scripted run, long enough to level up
+    let exit: AppExit = App::new()
+        .add_plugins(GamePlugin::headless(
+            HeadlessRun::seconds(75.0)
+                .with_input(Path::new(env!("CARGO_MANIFEST_DIR")).join("scripts/level_up.ron"))
+                .with_recording(&path),
+        ))
+        .run();
+    assert!(exit.is_success());
+
+    // Check the run got past level 1, so playback has to switch level on the same tick
+    let replay = Replay::load(&path);
+    assert!(replay.is_ok_and(|replay| replay.result.is_some_and(|result| result.level > 1)));
+
+    // Play it back, failing if it doesn't end on the recorded result
+    let exit: AppExit = App::new()
+        .add_plugins(GamePlugin::headless(HeadlessRun::ticks(u64::MAX)).replay(&path))
+        .run();
+    fs::remove_file(&path).ok();
+    assert!(exit.is_success());
+}